@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::error::OrderError;
+use crate::instrument;
+use crate::order::{Order, Side};
+
+/// What an account holds of one asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Balance {
+    pub(crate) available: Decimal,  // Free to withdraw or commit to new orders
+    pub(crate) reserved: Decimal,   // Held by open orders
+}
+
+impl Balance {
+    pub fn available(&self) -> Decimal {
+        self.available
+    }
+
+    pub fn reserved(&self) -> Decimal {
+        self.reserved
+    }
+
+    pub fn total(&self) -> Decimal {
+        self.available + self.reserved
+    }
+}
+
+/// Funds an open order holds: quote currency for a bid, base currency for
+/// an ask.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Reservation {
+    pub(crate) account: String,
+    pub(crate) asset: String,
+    pub(crate) amount: Decimal,
+}
+
+/// Balances of every account, per asset.
+#[derive(Debug, Clone, Default)]
+pub struct AccountLedger {
+    balances: HashMap<(String, String), Balance>,  // By (account, asset)
+}
+
+impl AccountLedger {
+    pub fn new() -> Self {
+        AccountLedger::default()
+    }
+
+    /// The account's balance of `asset`, zero if it never held any.
+    pub fn balance(&self, account: &str, asset: &str) -> Balance {
+        self.balances
+            .get(&(account.to_string(), asset.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn deposit(&mut self, account: &str, asset: &str, amount: Decimal) -> Result<(), OrderError> {
+        if amount <= Decimal::ZERO {
+            return Err(OrderError::InvalidQuantity(amount));
+        }
+        self.credit(account, asset, amount);
+        Ok(())
+    }
+
+    /// Takes `amount` out of the available balance; reserved funds can't be
+    /// withdrawn until their orders finish.
+    pub fn withdraw(&mut self, account: &str, asset: &str, amount: Decimal) -> Result<(), OrderError> {
+        if amount <= Decimal::ZERO {
+            return Err(OrderError::InvalidQuantity(amount));
+        }
+        let balance = self.available_at_least(account, asset, amount)?;
+        balance.available -= amount;
+        Ok(())
+    }
+
+    /// Applies a change in what a custody source such as a `Vault` holds
+    /// for the account: a deposit if `change` is positive, a withdrawal from
+    /// the available balance if negative. Only the change is applied, so
+    /// funds the account has since reserved, spent or received on the
+    /// exchange are left as they are.
+    pub fn apply_custody(&mut self, account: &str, asset: &str, change: Decimal) -> Result<(), OrderError> {
+        match change.cmp(&Decimal::ZERO) {
+            Ordering::Greater => self.deposit(account, asset, change),
+            Ordering::Less => self.withdraw(account, asset, -change),
+            Ordering::Equal => Ok(()),
+        }
+    }
+
+    pub(crate) fn credit(&mut self, account: &str, asset: &str, amount: Decimal) {
+        self.entry(account, asset).available += amount;
+    }
+
+    /// Takes `amount` out of the available balance; the caller has checked
+    /// it is there.
+    pub(crate) fn debit(&mut self, account: &str, asset: &str, amount: Decimal) {
+        self.entry(account, asset).available -= amount;
+    }
+
+    /// Moves `amount` from available to reserved.
+    pub(crate) fn reserve(&mut self, account: &str, asset: &str, amount: Decimal) -> Result<(), OrderError> {
+        let balance = self.available_at_least(account, asset, amount)?;
+        balance.available -= amount;
+        balance.reserved += amount;
+        Ok(())
+    }
+
+    /// Moves `amount` from reserved back to available.
+    pub(crate) fn release(&mut self, account: &str, asset: &str, amount: Decimal) {
+        let balance = self.entry(account, asset);
+        balance.reserved -= amount;
+        balance.available += amount;
+    }
+
+    /// Pays `amount` out of reserved funds.
+    pub(crate) fn spend_reserved(&mut self, account: &str, asset: &str, amount: Decimal) {
+        self.entry(account, asset).reserved -= amount;
+    }
+
+    fn entry(&mut self, account: &str, asset: &str) -> &mut Balance {
+        self.balances
+            .entry((account.to_string(), asset.to_string()))
+            .or_default()
+    }
+
+    fn available_at_least(
+        &mut self,
+        account: &str,
+        asset: &str,
+        required: Decimal,
+    ) -> Result<&mut Balance, OrderError> {
+        let balance = self.entry(account, asset);
+        if balance.available < required {
+            return Err(OrderError::InsufficientFunds {
+                account: account.to_string(),
+                asset: asset.to_string(),
+                required,
+                available: balance.available,
+            });
+        }
+        Ok(balance)
+    }
+}
+
+/// Funds `order` needs to stay open: its limit price times its remaining
+/// quantity for a bid, its remaining quantity for an ask. A bid without a
+/// limit price is refused, since its cost can't be known up front.
+pub(crate) fn required_funds(order: &Order) -> Result<Decimal, OrderError> {
+    match order.side {
+        Side::Bid => {
+            let price = order.price.ok_or_else(|| OrderError::UnpricedBid(order.id.clone()))?;
+            instrument::notional(price, order.quantity)
+        }
+        Side::Ask => Ok(order.quantity),
+    }
+}
@@ -0,0 +1,108 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+
+use crate::level::PriceLevel;
+use crate::matching;
+
+/// How a book turns orders into trades.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MarketMode {
+    #[default]
+    Continuous,         // Every order matches as it arrives
+    BatchAuction(u64),  // Orders collect and clear together whenever book time reaches a multiple of this interval
+}
+
+impl MarketMode {
+    pub(crate) fn batch_interval(self) -> Option<u64> {
+        match self {
+            MarketMode::Continuous => None,
+            MarketMode::BatchAuction(interval) => Some(interval.max(1)),
+        }
+    }
+}
+
+/// Where a call auction uncrosses the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Clearing {
+    pub(crate) price: Decimal,
+    pub(crate) volume: Decimal,
+}
+
+/// Finds the single price at which the most quantity trades between
+/// `bids` and `asks`. Ties go to the smallest imbalance between demand
+/// and supply at the price, then to the price closest to `reference`, then
+/// to the lower price. `None` if the book doesn't cross.
+pub(crate) fn clearing_price(
+    bids: &BTreeMap<Decimal, PriceLevel>,
+    asks: &BTreeMap<Decimal, PriceLevel>,
+    reference: Option<Decimal>,
+) -> Option<Clearing> {
+    let mut prices: Vec<Decimal> = bids.keys().chain(asks.keys()).copied().collect();
+    prices.sort();
+    prices.dedup();
+
+    let total_demand: Decimal = bids.values().map(PriceLevel::quantity).sum();
+    let mut below_demand = Decimal::ZERO;  // Bids priced under the candidate
+    let mut supply = Decimal::ZERO;        // Asks priced at or under the candidate
+    let mut bids_up = bids.iter().peekable();
+    let mut asks_up = asks.iter().peekable();
+    let mut best: Option<(Clearing, Decimal)> = None;  // With its imbalance
+    for price in prices {
+        while let Some((_, level)) = bids_up.next_if(|(bid, _)| **bid < price) {
+            below_demand += level.quantity();
+        }
+        while let Some((_, level)) = asks_up.next_if(|(ask, _)| **ask <= price) {
+            supply += level.quantity();
+        }
+        let demand = total_demand - below_demand;
+        let candidate = Clearing {
+            price,
+            volume: demand.min(supply),
+        };
+        let imbalance = (demand - supply).abs();
+        let rank = |clearing: &Clearing, imbalance: Decimal| {
+            let distance = reference.map_or(Decimal::ZERO, |reference| (clearing.price - reference).abs());
+            (Reverse(clearing.volume), imbalance, distance, clearing.price)
+        };
+        if best.as_ref().is_none_or(|(best, best_imbalance)| {
+            rank(&candidate, imbalance) < rank(best, *best_imbalance)
+        }) {
+            best = Some((candidate, imbalance));
+        }
+    }
+    best.map(|(clearing, _)| clearing)
+        .filter(|clearing| clearing.volume > Decimal::ZERO)
+}
+
+/// Picks the orders on one side that trade `volume` in a call auction:
+/// whole levels best price first, with the level where the volume runs out
+/// rationed pro-rata in multiples of `lot_size`. Returns `(price, seq,
+/// quantity)` in priority order.
+pub(crate) fn allocate<'a>(
+    levels: impl Iterator<Item = (&'a Decimal, &'a PriceLevel)>,
+    volume: Decimal,
+    lot_size: Decimal,
+) -> Vec<(Decimal, u64, Decimal)> {
+    let mut allocations = Vec::new();
+    let mut left = volume;
+    for (price, level) in levels {
+        if left.is_zero() {
+            break;
+        }
+        let entries: Vec<_> = level.entries().collect();
+        let quantities: Vec<Decimal> = entries.iter().map(|(_, order)| order.quantity).collect();
+        let fills = if level.quantity() <= left {
+            quantities
+        } else {
+            matching::ration(lot_size, &quantities, left)
+        };
+        for ((seq, _), fill) in entries.iter().zip(fills) {
+            if fill > Decimal::ZERO {
+                allocations.push((*price, *seq, fill));
+                left -= fill;
+            }
+        }
+    }
+    allocations
+}
@@ -0,0 +1,969 @@
+use std::collections::{BTreeMap, HashMap};
+use rust_decimal::Decimal;
+
+use crate::auction::{self, MarketMode};
+use crate::depth::{BookDelta, DeltaAction, Depth, DepthLevel};
+use crate::error::OrderError;
+use crate::instrument::{self, InstrumentSpec};
+use crate::level::{OrderLocation, PriceLevel};
+use crate::order::{Order, OrderType, Side, TimeInForce};
+use crate::report::{ExecutionReport, ReportKind};
+use crate::matching::{self, Fifo, MatchingPolicy};
+use crate::self_trade::SelfTradePrevention;
+use crate::snapshot::BookSnapshot;
+use crate::stop::StopBook;
+use crate::trade::{Execution, Trade};
+
+pub struct OrderBook {
+    asks: BTreeMap<Decimal, PriceLevel>,    // Sell orders sorted by price ascending
+    bids: BTreeMap<Decimal, PriceLevel>,    // Buy orders, best (highest) price last
+    index: HashMap<String, OrderLocation>,  // Every resting order by id
+    stops: StopBook,                        // Stop orders waiting for their trigger
+    expiries: BTreeMap<u64, Vec<String>>,   // Good-till-time order ids by expiry
+    last_price: Option<Decimal>,
+    reports: Vec<ExecutionReport>,          // Waiting for `drain_reports`
+    deltas: Vec<BookDelta>,                 // Waiting for `drain_deltas`
+    // Levels changed by the current call, as they were before it
+    touched: HashMap<(Side, Decimal), Option<DepthLevel>>,
+    sequence: u64,                          // Sequence of the last published delta
+    engine_sequence: u64,                   // Sequence of the last book command
+    last_trade_id: u64,
+    clock: u64,                             // Latest order timestamp seen
+    spec: InstrumentSpec,
+    self_trade_prevention: SelfTradePrevention,
+    policy: Box<dyn MatchingPolicy>,
+    mode: MarketMode,
+}
+
+impl Default for OrderBook {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OrderBook {
+    /// Creates a book that accepts any positive price.
+    pub fn new() -> Self {
+        OrderBook {
+            asks: BTreeMap::new(),
+            bids: BTreeMap::new(),
+            index: HashMap::new(),
+            stops: StopBook::default(),
+            expiries: BTreeMap::new(),
+            last_price: None,
+            reports: Vec::new(),
+            deltas: Vec::new(),
+            touched: HashMap::new(),
+            sequence: 0,
+            engine_sequence: 0,
+            last_trade_id: 0,
+            clock: 0,
+            spec: InstrumentSpec::new(),
+            self_trade_prevention: SelfTradePrevention::default(),
+            policy: Box::new(Fifo),
+            mode: MarketMode::Continuous,
+        }
+    }
+
+    /// Creates a book that only accepts prices on multiples of `tick_size`,
+    /// which must be positive.
+    pub fn with_tick_size(tick_size: Decimal) -> Result<Self, OrderError> {
+        Self::with_spec(InstrumentSpec::new().with_tick_size(tick_size))
+    }
+
+    /// Creates a book that validates every order against `spec`, refusing
+    /// a spec whose rules can't be applied.
+    pub fn with_spec(spec: InstrumentSpec) -> Result<Self, OrderError> {
+        spec.validate()?;
+        Ok(OrderBook {
+            spec,
+            ..Self::new()
+        })
+    }
+
+    /// Rebuilds a book from a level-3 snapshot, with every order back in its
+    /// queue position. Matching policy and self-trade prevention are not part
+    /// of the snapshot and start out at their defaults.
+    pub fn from_snapshot(snapshot: &BookSnapshot) -> Result<Self, OrderError> {
+        let mut book = OrderBook::with_spec(snapshot.spec.clone())?;
+        book.last_price = snapshot.last_price;
+        book.sequence = snapshot.sequence;
+        book.engine_sequence = snapshot.engine_sequence;
+        book.last_trade_id = snapshot.last_trade_id;
+        book.clock = snapshot.clock;
+        // Batch auctions also hold immediate-or-cancel orders until they clear
+        let batch = snapshot.market_mode.batch_interval().is_some();
+
+        for order in snapshot.bids.iter().chain(&snapshot.asks) {
+            book.validate_structure(order)?;
+            let rests = order.can_rest() || (batch && order.order_type == OrderType::Limit);
+            if !rests {
+                return Err(OrderError::CannotRest(order.id.clone()));
+            }
+            book.enqueue(order.clone());
+        }
+        book.touched.clear();
+        for order in &snapshot.stops {
+            book.validate_structure(order)?;
+            if !order.is_stop() {
+                return Err(OrderError::CannotRest(order.id.clone()));
+            }
+            book.hold_stop(order.clone());
+        }
+        book.mode = snapshot.market_mode;
+        Ok(book)
+    }
+
+    /// Exports every resting order in queue order, plus pending stops.
+    pub fn snapshot(&self) -> BookSnapshot {
+        let side = |side| {
+            self.levels(side)
+                .flat_map(|(_, level)| level.entries().map(|(_, order)| order.clone()))
+                .collect()
+        };
+        BookSnapshot {
+            sequence: self.sequence,
+            engine_sequence: self.engine_sequence,
+            last_trade_id: self.last_trade_id,
+            clock: self.clock,
+            spec: self.spec.clone(),
+            market_mode: self.mode,
+            last_price: self.last_price,
+            bids: side(Side::Bid),
+            asks: side(Side::Ask),
+            stops: self.stops.orders().cloned().collect(),
+        }
+    }
+
+    pub fn spec(&self) -> &InstrumentSpec {
+        &self.spec
+    }
+
+    pub fn tick_size(&self) -> Option<Decimal> {
+        self.spec.tick_size()
+    }
+
+    pub fn self_trade_prevention(&self) -> SelfTradePrevention {
+        self.self_trade_prevention
+    }
+
+    /// Chooses what happens when orders from the same account would trade.
+    /// Orders without an account never count as self-trades.
+    pub fn set_self_trade_prevention(&mut self, mode: SelfTradePrevention) {
+        self.self_trade_prevention = mode;
+    }
+
+    /// Chooses how incoming quantity is shared among the orders at a price
+    /// level. Books start out with price-time priority (`Fifo`). A policy
+    /// that rounds fills must round to whole lots of the instrument's lot
+    /// size, or it could leave orders holding part of a lot.
+    pub fn set_matching_policy(&mut self, policy: impl MatchingPolicy + 'static) -> Result<(), OrderError> {
+        if let (Some(policy_lot_size), Some(lot_size)) = (policy.lot_size(), self.spec.lot_size()) {
+            if policy_lot_size.is_zero() || !(policy_lot_size % lot_size).is_zero() {
+                return Err(OrderError::PolicyLotMismatch { policy_lot_size, lot_size });
+            }
+        }
+        self.policy = Box::new(policy);
+        Ok(())
+    }
+
+    /// Whether the book matches continuously or in batch auctions.
+    pub fn market_mode(&self) -> MarketMode {
+        self.mode
+    }
+
+    /// Switches between continuous matching and batch auctions. Leaving
+    /// batch auction mode clears the orders collected so far first, and
+    /// returns that auction's trades along with any stops it sets off.
+    pub fn set_market_mode(&mut self, mode: MarketMode) -> Execution {
+        self.engine_sequence += 1;
+        let mut execution = Execution::default();
+        if self.mode.batch_interval().is_some() && mode.batch_interval().is_none() {
+            execution = self.clear_batch();
+        }
+        self.mode = mode;
+        self.release_stops(&mut execution);
+        self.publish_deltas();
+        execution
+    }
+
+    /// Price of the most recent trade, which stop orders trigger on.
+    pub fn last_price(&self) -> Option<Decimal> {
+        self.last_price
+    }
+
+    pub fn best_bid(&self) -> Option<DepthLevel> {
+        let (price, level) = self.bids.iter().next_back()?;
+        Some(DepthLevel::from_level(*price, level))
+    }
+
+    pub fn best_ask(&self) -> Option<DepthLevel> {
+        let (price, level) = self.asks.iter().next()?;
+        Some(DepthLevel::from_level(*price, level))
+    }
+
+    /// Best ask minus best bid, when both sides are populated.
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_price(Side::Ask)? - self.best_price(Side::Bid)?)
+    }
+
+    /// Midpoint between the best bid and best ask, when both sides are populated.
+    pub fn mid(&self) -> Option<Decimal> {
+        Some((self.best_price(Side::Ask)? + self.best_price(Side::Bid)?) / Decimal::TWO)
+    }
+
+    /// The best `n` price levels of each side.
+    pub fn depth(&self, n: usize) -> Depth {
+        let side = |side| {
+            self.levels(side)
+                .take(n)
+                .map(|(price, level)| DepthLevel::from_level(*price, level))
+                .collect()
+        };
+        Depth {
+            bids: side(Side::Bid),
+            asks: side(Side::Ask),
+        }
+    }
+
+    /// Like `depth`, but each level carries the running totals from the
+    /// touch down to and including that level.
+    pub fn cumulative_depth(&self, n: usize) -> Depth {
+        self.depth(n).accumulate()
+    }
+
+    /// Hands over the execution reports emitted since the last call, oldest
+    /// first. Reports pile up until drained.
+    pub fn drain_reports(&mut self) -> Vec<ExecutionReport> {
+        std::mem::take(&mut self.reports)
+    }
+
+    /// Hands over the level changes published since the last call, in
+    /// sequence order. Deltas pile up until drained.
+    pub fn drain_deltas(&mut self) -> Vec<BookDelta> {
+        std::mem::take(&mut self.deltas)
+    }
+
+    /// Sequence number of the last published book delta.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Sequence number of the last command (add, cancel, amend or expire)
+    /// the book processed, rejected ones included.
+    pub fn engine_sequence(&self) -> u64 {
+        self.engine_sequence
+    }
+
+    /// Book time: the latest order timestamp or expiry time seen.
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    /// Looks up a resting order, or a stop order waiting for its trigger, by id.
+    pub fn order(&self, id: &str) -> Option<&Order> {
+        let Some(location) = self.index.get(id) else {
+            return self.stops.get(id);
+        };
+        let levels = match location.side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        };
+        levels.get(&location.price)?.get(location.seq)
+    }
+
+    /// Matches `order` against the book and rests or cancels the remainder
+    /// according to its type and time in force. Stop orders are held back
+    /// until a trade reaches their stop price.
+    ///
+    /// Every stop set off by the resulting trades is released into matching
+    /// within the same call, including stops triggered by those releases;
+    /// their trades are appended to the returned execution.
+    ///
+    /// The order's timestamp doubles as the book clock: good-till-time orders
+    /// that have expired by then are removed before matching.
+    ///
+    /// In batch auction mode the order is collected for the next auction
+    /// instead of matching, and only plain limit orders are accepted. If its
+    /// timestamp is past the end of the current batch, that batch is cleared
+    /// first and its trades lead the returned execution.
+    pub fn add_order(&mut self, mut order: Order) -> Result<Execution, OrderError> {
+        self.engine_sequence += 1;
+        // Reject before the clock moves, so a refused order expires nothing
+        // and clears no auction the caller would never hear about
+        let now = self.clock.max(order.timestamp);
+        let checked = self
+            .validate(&order)
+            .and_then(|()| self.apply_post_only(&mut order, now));
+        if let Err(error) = checked {
+            self.reports.push(ExecutionReport::new(&order, ReportKind::Rejected(error.clone())));
+            return Err(error);
+        }
+        let auction = self.advance_clock(order.timestamp);
+
+        let mut execution = if !order.is_stop() {
+            self.execute(order, Some(ReportKind::Accepted))
+                .expect("post-only was checked before the clock moved")
+        } else if self.last_price.is_some_and(|last| order.is_triggered_by(last)) {
+            self.execute(order.into_triggered(), Some(ReportKind::Accepted))
+                .expect("stop orders are never post-only")
+        } else {
+            self.reports.push(ExecutionReport::new(&order, ReportKind::Accepted));
+            self.hold_stop(order);
+            Execution::default()
+        };
+        execution.trades.splice(0..0, auction.trades);
+        execution.cancelled_order_ids.splice(0..0, auction.cancelled_order_ids);
+        execution.expired_orders = auction.expired_orders;
+
+        self.release_stops(&mut execution);
+        self.publish_deltas();
+        Ok(execution)
+    }
+
+    /// Matches an order that is live (not a pending stop) and rests or
+    /// cancels what is left. `entry` is reported once the order is let in.
+    fn execute(&mut self, mut order: Order, entry: Option<ReportKind>) -> Result<Execution, OrderError> {
+        if self.mode.batch_interval().is_some() {
+            // Collected for the next auction; crossing orders wait there too
+            let mut execution = Execution {
+                resting_quantity: order.quantity,
+                ..Execution::default()
+            };
+            if let Some(kind) = entry {
+                execution.reports.push(ExecutionReport::new(&order, kind));
+            }
+            self.rest(order);
+            self.reports.append(&mut execution.reports);
+            return Ok(execution);
+        }
+
+        if let Err(error) = self.apply_post_only(&mut order, self.clock) {
+            self.reports.push(ExecutionReport::new(&order, ReportKind::Rejected(error.clone())));
+            return Err(error);
+        }
+
+        let mut execution = Execution::default();
+        if let Some(kind) = entry {
+            execution.reports.push(ExecutionReport::new(&order, kind));
+        }
+
+        if order.time_in_force == TimeInForce::Fok && !self.can_fill(&order) {
+            execution.cancelled_quantity = order.quantity;
+            execution.reports.push(ExecutionReport::new(&order, ReportKind::Cancelled));
+            self.reports.append(&mut execution.reports);
+            return Ok(execution);
+        }
+
+        self.match_order(&mut order, &mut execution);
+        execution.filled_quantity = execution.trades.iter().map(|trade| trade.quantity).sum();
+
+        // If there's remaining quantity, either rest it or cancel it
+        if order.quantity > Decimal::ZERO {
+            if order.can_rest() {
+                execution.resting_quantity = order.quantity;
+                self.rest(order);
+            } else {
+                execution.cancelled_quantity += order.quantity;
+                execution.reports.push(ExecutionReport::new(&order, ReportKind::Cancelled));
+            }
+        }
+
+        if let Some(trade) = execution.trades.last() {
+            self.last_price = Some(trade.price);
+        }
+        self.reports.append(&mut execution.reports);
+        Ok(execution)
+    }
+
+    /// Keeps a stop order out of sight until its trigger trades.
+    fn hold_stop(&mut self, order: Order) {
+        if let TimeInForce::Gtt(expires_at) = order.time_in_force {
+            self.expiries.entry(expires_at).or_default().push(order.id.clone());
+        }
+        self.stops.insert(order);
+    }
+
+    /// Releases every stop set off by the last price into matching until no
+    /// triggered stops remain.
+    ///
+    /// Stops stay held while the book runs batch auctions.
+    fn release_stops(&mut self, execution: &mut Execution) {
+        if self.mode.batch_interval().is_some() {
+            return;
+        }
+        while let Some(last_price) = self.last_price {
+            let Some(stop) = self.stops.pop_triggered(last_price) else {
+                break;
+            };
+            execution.triggered_order_ids.push(stop.id.clone());
+            let released = self
+                .execute(stop.into_triggered(), None)
+                .expect("stop orders are never post-only");
+            execution.trades.extend(released.trades);
+        }
+    }
+
+    /// Removes a resting order, or a stop order waiting for its trigger, and
+    /// returns it with its unfilled quantity.
+    pub fn cancel_order(&mut self, id: &str) -> Result<Order, OrderError> {
+        self.engine_sequence += 1;
+        let order = self.remove_order(id)?;
+        self.reports.push(ExecutionReport::new(&order, ReportKind::Cancelled));
+        self.publish_deltas();
+        Ok(order)
+    }
+
+    /// Hands out the next trade id. A `DarkPool` crossing at this book's
+    /// midpoint draws from it too, so ids are unique across both.
+    pub(crate) fn next_trade_id(&mut self) -> u64 {
+        self.last_trade_id += 1;
+        self.last_trade_id
+    }
+
+    /// Reports an order the engine refused before it reached the book. The
+    /// refusal still takes an engine sequence, as a book rejection would.
+    pub(crate) fn reject(&mut self, order: &Order, error: OrderError) {
+        self.engine_sequence += 1;
+        self.reports.push(ExecutionReport::new(order, ReportKind::Rejected(error)));
+    }
+
+    /// Reports an amend of order `id` the engine refused before it reached
+    /// the book.
+    pub(crate) fn reject_amend(&mut self, id: &str, error: OrderError) {
+        self.engine_sequence += 1;
+        let report = ExecutionReport::amend_rejected(id, self.order(id), error);
+        self.reports.push(report);
+    }
+
+    /// Takes an order off the book or out of the stop book without reporting it.
+    fn remove_order(&mut self, id: &str) -> Result<Order, OrderError> {
+        let Some(location) = self.index.remove(id) else {
+            return self
+                .stops
+                .remove(id)
+                .ok_or_else(|| OrderError::OrderNotFound(id.to_string()));
+        };
+        self.touch(location.side, location.price);
+        let levels = self.levels_mut(location.side);
+        let level = levels
+            .get_mut(&location.price)
+            .expect("indexed order has a price level");
+        let order = level
+            .take(location.seq)
+            .expect("indexed order is in its price level");
+        if level.is_empty() {
+            levels.remove(&location.price);
+        }
+        Ok(order)
+    }
+
+    /// Changes the price and/or remaining quantity of a resting order.
+    ///
+    /// Reducing the quantity at the same price keeps the order's place in the
+    /// queue. Any price change or quantity increase costs time priority: the
+    /// order is pulled and re-entered into matching, so it may trade
+    /// immediately if the new price crosses. A refused amend is reported as
+    /// `ReportKind::Rejected` and leaves the order as it was.
+    pub fn amend_order(
+        &mut self,
+        id: &str,
+        new_price: Decimal,
+        new_quantity: Decimal,
+    ) -> Result<Execution, OrderError> {
+        self.engine_sequence += 1;
+        let result = self.try_amend(id, new_price, new_quantity);
+        if let Err(error) = &result {
+            let report = ExecutionReport::amend_rejected(id, self.order(id), error.clone());
+            self.reports.push(report);
+        }
+        result
+    }
+
+    fn try_amend(
+        &mut self,
+        id: &str,
+        new_price: Decimal,
+        new_quantity: Decimal,
+    ) -> Result<Execution, OrderError> {
+        self.spec.validate_price(new_price)?;
+        self.spec.validate_quantity(new_quantity)?;
+        self.spec.validate_notional(new_price, new_quantity)?;
+        let location = *self
+            .index
+            .get(id)
+            .ok_or_else(|| OrderError::OrderNotFound(id.to_string()))?;
+
+        let mut amended = self.order(id).cloned().expect("indexed order is in its price level");
+        if new_price == location.price && new_quantity <= amended.quantity {
+            self.touch(location.side, location.price);
+            let order = self
+                .levels_mut(location.side)
+                .get_mut(&location.price)
+                .and_then(|level| level.update(location.seq, |order| order.reduce_to(new_quantity)))
+                .expect("indexed order is in its price level");
+            let report = ExecutionReport::new(order, ReportKind::Replaced);
+            self.reports.push(report);
+            self.publish_deltas();
+            return Ok(Execution { resting_quantity: new_quantity, ..Execution::default() });
+        }
+
+        // Check post-only before pulling the order so a rejection leaves it in place
+        amended.price = Some(new_price);
+        amended.quantity = new_quantity;
+        self.apply_post_only(&mut amended, self.clock)?;
+        self.remove_order(id).expect("indexed order can be removed");
+        let mut execution = self
+            .execute(amended, Some(ReportKind::Replaced))
+            .expect("post-only was checked before the order was pulled");
+        self.release_stops(&mut execution);
+        self.publish_deltas();
+        Ok(execution)
+    }
+
+    /// Advances the book clock to `now`, cancels every good-till-time order
+    /// that has expired by then and returns them.
+    ///
+    /// In batch auction mode a batch that closed by `now` is cleared too; use
+    /// `run_auction` to receive its trades.
+    pub fn expire_orders(&mut self, now: u64) -> Vec<Order> {
+        self.run_auction(now).expired_orders
+    }
+
+    /// Advances the book clock to `now` like `expire_orders`, and in batch
+    /// auction mode clears the current batch if it closed by then. Meant to
+    /// be driven by a timer so batches clear on time without new orders.
+    pub fn run_auction(&mut self, now: u64) -> Execution {
+        self.engine_sequence += 1;
+        let execution = self.advance_clock(now);
+        self.publish_deltas();
+        execution
+    }
+
+    /// Moves the book clock forward to `now`. A batch auction that closed
+    /// on the way is cleared at its closing time, and good-till-time orders
+    /// are expired up to each point.
+    fn advance_clock(&mut self, now: u64) -> Execution {
+        let mut expired = Vec::new();
+        let mut execution = Execution::default();
+        if let Some(interval) = self.mode.batch_interval() {
+            let close = now / interval * interval;
+            if close > self.clock {
+                self.clock = close;
+                expired = self.expire(close);
+                execution = self.clear_batch();
+            }
+        }
+        self.clock = self.clock.max(now);
+        expired.extend(self.expire(self.clock));
+        execution.expired_orders = expired;
+        execution
+    }
+
+    /// Uncrosses the whole book at a single price, as a call auction does:
+    /// the price that trades the most, with orders there rationed pro-rata.
+    /// Immediate-or-cancel orders left over are cancelled. Self-trade
+    /// prevention does not apply.
+    fn clear_batch(&mut self) -> Execution {
+        let mut execution = Execution::default();
+        if let Some(clearing) = auction::clearing_price(&self.bids, &self.asks, self.last_price) {
+            let lot_size = self.spec.lot_size().unwrap_or(Decimal::ZERO);
+            let bids = auction::allocate(self.levels(Side::Bid), clearing.volume, lot_size);
+            let asks = auction::allocate(self.levels(Side::Ask), clearing.volume, lot_size);
+            self.uncross(clearing.price, &bids, &asks, &mut execution);
+            self.last_price = Some(clearing.price);
+        }
+
+        let leftovers: Vec<String> = self
+            .levels(Side::Bid)
+            .chain(self.levels(Side::Ask))
+            .flat_map(|(_, level)| level.entries())
+            .filter(|(_, order)| order.time_in_force == TimeInForce::Ioc)
+            .map(|(_, order)| order.id.clone())
+            .collect();
+        for id in leftovers {
+            let order = self.remove_order(&id).expect("leftover order is on the book");
+            execution.cancelled_quantity += order.quantity;
+            execution.reports.push(ExecutionReport::new(&order, ReportKind::Cancelled));
+            execution.cancelled_order_ids.push(order.id);
+        }
+        self.reports.append(&mut execution.reports);
+        execution
+    }
+
+    /// Trades the auction allocations of both sides against each other at
+    /// `price`, pairing them off in priority order. The order that reached
+    /// the book first counts as the maker.
+    fn uncross(
+        &mut self,
+        price: Decimal,
+        bids: &[(Decimal, u64, Decimal)],
+        asks: &[(Decimal, u64, Decimal)],
+        execution: &mut Execution,
+    ) {
+        for (side, allocations) in [(Side::Bid, bids), (Side::Ask, asks)] {
+            for (level_price, _, _) in allocations {
+                self.touch(side, *level_price);
+            }
+        }
+
+        let (mut bid_index, mut ask_index) = (0, 0);
+        let (mut bid_left, mut ask_left) = (Decimal::ZERO, Decimal::ZERO);
+        while bid_index < bids.len() && ask_index < asks.len() {
+            let (bid_price, bid_seq, bid_quantity) = bids[bid_index];
+            let (ask_price, ask_seq, ask_quantity) = asks[ask_index];
+            let quantity = (bid_quantity - bid_left).min(ask_quantity - ask_left);
+            let bid = self.fill_resting(Side::Bid, bid_price, bid_seq, quantity);
+            let ask = self.fill_resting(Side::Ask, ask_price, ask_seq, quantity);
+
+            let (maker, taker) = if bid.timestamp < ask.timestamp { (&bid, &ask) } else { (&ask, &bid) };
+            execution.trades.push(Trade {
+                id: 0,
+                maker_order_id: maker.id.clone(),
+                taker_order_id: taker.id.clone(),
+                maker_account: maker.account.clone(),
+                taker_account: taker.account.clone(),
+                aggressor: taker.side,
+                price,
+                quantity,
+                maker_remaining: maker.quantity,
+                taker_remaining: taker.quantity,
+                timestamp: 0,
+                sequence: 0,
+                settled: false,
+            });
+            execution.reports.push(ExecutionReport::fill(&bid, price, quantity));
+            execution.reports.push(ExecutionReport::fill(&ask, price, quantity));
+
+            bid_left += quantity;
+            ask_left += quantity;
+            if bid_left == bid_quantity {
+                bid_index += 1;
+                bid_left = Decimal::ZERO;
+            }
+            if ask_left == ask_quantity {
+                ask_index += 1;
+                ask_left = Decimal::ZERO;
+            }
+        }
+        self.stamp_trades(execution, 0);
+
+        for (side, allocations) in [(Side::Bid, bids), (Side::Ask, asks)] {
+            for (level_price, seq, _) in allocations {
+                let levels = self.levels_mut(side);
+                let level = levels.get_mut(level_price).expect("allocated level is on the book");
+                let filled = level
+                    .update(*seq, Order::refresh_display)
+                    .is_some_and(|order| order.quantity.is_zero());
+                if filled {
+                    let order = level.take(*seq).expect("allocated order is on the book");
+                    if level.is_empty() {
+                        levels.remove(level_price);
+                    }
+                    self.index.remove(&order.id);
+                }
+            }
+        }
+    }
+
+    /// Fills a resting order in place and returns a copy of it afterwards.
+    fn fill_resting(&mut self, side: Side, price: Decimal, seq: u64, quantity: Decimal) -> Order {
+        self.levels_mut(side)
+            .get_mut(&price)
+            .and_then(|level| level.update(seq, |order| order.fill(quantity)))
+            .expect("allocated order is on the book")
+            .clone()
+    }
+
+    fn expire(&mut self, now: u64) -> Vec<Order> {
+        let mut expired = Vec::new();
+        while let Some(entry) = self.expiries.first_entry() {
+            if *entry.key() > now {
+                break;
+            }
+            for id in entry.remove() {
+                // The id may have been filled, cancelled or reused since it was queued
+                if self.order(&id).is_some_and(|order| order.is_expired(now)) {
+                    if let Ok(order) = self.remove_order(&id) {
+                        self.reports.push(ExecutionReport::new(&order, ReportKind::Expired));
+                        expired.push(order);
+                    }
+                }
+            }
+        }
+        expired
+    }
+
+    fn validate(&self, order: &Order) -> Result<(), OrderError> {
+        self.validate_structure(order)?;
+        self.spec.validate_quantity(order.quantity)?;
+        // Market and stop orders have no price to value them at
+        if let Some(price) = order.price {
+            self.spec.validate_notional(price, order.quantity)?;
+        }
+        if self.mode.batch_interval().is_some() {
+            if order.order_type != OrderType::Limit {
+                return Err(OrderError::UnsupportedOrderType(order.order_type));
+            }
+            if matches!(
+                order.time_in_force,
+                TimeInForce::Fok | TimeInForce::PostOnly | TimeInForce::PostOnlySlide
+            ) {
+                return Err(OrderError::InvalidTimeInForce(order.time_in_force));
+            }
+        }
+        Ok(())
+    }
+
+    /// The rules an order must keep for as long as it is on the book, which
+    /// is all a restored order is held to: the entry-size rules no longer
+    /// apply once it has partly filled.
+    fn validate_structure(&self, order: &Order) -> Result<(), OrderError> {
+        if let Some(price) = order.price {
+            self.spec.validate_price(price)?;
+            instrument::notional(price, order.quantity)?;
+        }
+        if let Some(stop_price) = order.stop_price {
+            self.spec.validate_price(stop_price)?;
+        }
+        self.spec.validate_lot(order.quantity)?;
+        if let Some(display_quantity) = order.display_quantity {
+            self.spec.validate_lot(display_quantity)?;
+        }
+        match order.time_in_force {
+            TimeInForce::PostOnly | TimeInForce::PostOnlySlide | TimeInForce::Gtt(_)
+                if order.order_type == OrderType::Market =>
+            {
+                return Err(OrderError::InvalidTimeInForce(order.time_in_force));
+            }
+            TimeInForce::PostOnly | TimeInForce::PostOnlySlide if order.is_stop() => {
+                return Err(OrderError::InvalidTimeInForce(order.time_in_force));
+            }
+            TimeInForce::Gtt(expires_at) if expires_at <= order.timestamp => {
+                return Err(OrderError::InvalidTimeInForce(order.time_in_force));
+            }
+            _ => {}
+        }
+        if self.index.contains_key(&order.id) || self.stops.contains(&order.id) {
+            return Err(OrderError::DuplicateOrderId(order.id.clone()));
+        }
+        Ok(())
+    }
+
+    /// Rejects a post-only order that would trade on arrival, or slides it
+    /// one tick behind the opposite touch. Without a tick size there is no
+    /// price to slide to, so sliding orders are rejected as well.
+    ///
+    /// The touch is taken as it will be at book time `now`, past any
+    /// good-till-time orders expiring by then.
+    fn apply_post_only(&self, order: &mut Order, now: u64) -> Result<(), OrderError> {
+        let touch = self
+            .levels(order.side.opposite())
+            .find(|(_, level)| level.entries().any(|(_, resting)| !resting.is_expired(now)))
+            .map(|(price, _)| *price);
+        let Some(touch) = touch else {
+            return Ok(());
+        };
+        if !order.accepts(touch) {
+            return Ok(());
+        }
+        match order.time_in_force {
+            TimeInForce::PostOnly => Err(OrderError::WouldTakeLiquidity),
+            TimeInForce::PostOnlySlide => {
+                let tick_size = self.spec.tick_size().ok_or(OrderError::WouldTakeLiquidity)?;
+                let price = match order.side {
+                    Side::Bid => touch - tick_size,
+                    Side::Ask => touch + tick_size,
+                };
+                if price <= Decimal::ZERO {
+                    return Err(OrderError::WouldTakeLiquidity);
+                }
+                order.price = Some(price);
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the opposite side holds enough acceptable liquidity to fill
+    /// `order` completely. Unless self-trades are allowed, the order's own
+    /// resting orders don't count, and if meeting one would cut the order
+    /// short, nothing behind it counts either.
+    fn can_fill(&self, order: &Order) -> bool {
+        let prevention = self.self_trade_prevention;
+        let is_own = |resting: &Order| prevention != SelfTradePrevention::Allow && order.same_account(resting);
+        // Cancelling the resting order is the only prevention the taker outlives whole
+        let cuts_taker = !matches!(prevention, SelfTradePrevention::Allow | SelfTradePrevention::CancelOldest);
+        let mut available = Decimal::ZERO;
+        for (price, level) in self.levels(order.side.opposite()) {
+            if !order.accepts(*price) {
+                break;
+            }
+            // A policy that spreads fills over the level may meet any order in it
+            if cuts_taker
+                && !self.policy.fills_in_queue_order()
+                && level.entries().any(|(_, resting)| is_own(resting))
+            {
+                return false;
+            }
+            for (_, resting) in level.entries() {
+                if is_own(resting) {
+                    if cuts_taker {
+                        return false;
+                    }
+                    continue;
+                }
+                available += resting.quantity;
+                if available >= order.quantity {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    fn best_price(&self, side: Side) -> Option<Decimal> {
+        match side {
+            Side::Bid => self.bids.keys().next_back().copied(),
+            Side::Ask => self.asks.keys().next().copied(),
+        }
+    }
+
+    /// Trades `taker` against the opposite side, best price first, leaving
+    /// any remainder in `taker.quantity`.
+    fn match_order(&mut self, taker: &mut Order, execution: &mut Execution) {
+        let first_trade = execution.trades.len();
+        let side = taker.side.opposite();
+        while taker.quantity > Decimal::ZERO {
+            let Some(price) = self.best_price(side) else {
+                break;
+            };
+            if !taker.accepts(price) {
+                break;
+            }
+            self.touch(side, price);
+
+            let levels = match side {
+                Side::Bid => &mut self.bids,
+                Side::Ask => &mut self.asks,
+            };
+            let level = levels.get_mut(&price).expect("best price has a level");
+            let progressed = matching::match_level(
+                self.policy.as_ref(),
+                self.self_trade_prevention,
+                taker,
+                price,
+                level,
+                &mut self.index,
+                execution,
+            );
+
+            if level.is_empty() {
+                levels.remove(&price);
+            } else if !progressed {
+                break;
+            }
+        }
+
+        self.stamp_trades(execution, first_trade);
+    }
+
+    /// Numbers the trades from `first_trade` on and stamps them with the
+    /// book clock and the current command's sequence.
+    fn stamp_trades(&mut self, execution: &mut Execution, first_trade: usize) {
+        for trade in &mut execution.trades[first_trade..] {
+            trade.id = self.next_trade_id();
+            trade.timestamp = self.clock;
+            trade.sequence = self.engine_sequence;
+        }
+    }
+
+    /// Queues `order` at the back of its price level and indexes it.
+    fn rest(&mut self, mut order: Order) {
+        order.refresh_display();
+        self.enqueue(order);
+    }
+
+    /// Queues `order` exactly as it is, iceberg slice included.
+    fn enqueue(&mut self, order: Order) {
+        let id = order.id.clone();
+        let side = order.side;
+        let price = order.price.expect("only limit orders rest");
+        if let TimeInForce::Gtt(expires_at) = order.time_in_force {
+            self.expiries.entry(expires_at).or_default().push(id.clone());
+        }
+        self.touch(side, price);
+        let seq = self.levels_mut(side).entry(price).or_default().push_back(order);
+        self.index.insert(id, OrderLocation { side, price, seq });
+    }
+
+    /// Remembers how a level looked before the current call first changed it.
+    fn touch(&mut self, side: Side, price: Decimal) {
+        if !self.touched.contains_key(&(side, price)) {
+            let before = self.level_state(side, price);
+            self.touched.insert((side, price), before);
+        }
+    }
+
+    fn level_state(&self, side: Side, price: Decimal) -> Option<DepthLevel> {
+        let levels = match side {
+            Side::Bid => &self.bids,
+            Side::Ask => &self.asks,
+        };
+        levels.get(&price).map(|level| DepthLevel::from_level(price, level))
+    }
+
+    /// Turns the levels touched by the current call into sequenced deltas,
+    /// skipping any that ended up as they started. No queue sequences are
+    /// in flight between calls, so this is also where sparse levels get
+    /// compacted.
+    fn publish_deltas(&mut self) {
+        let mut touched: Vec<_> = self.touched.drain().collect();
+        touched.sort_by(|((side_a, price_a), _), ((side_b, price_b), _)| {
+            (*side_a == Side::Ask, price_a).cmp(&(*side_b == Side::Ask, price_b))
+        });
+
+        for ((side, price), before) in touched {
+            let levels = match side {
+                Side::Bid => &mut self.bids,
+                Side::Ask => &mut self.asks,
+            };
+            if let Some(level) = levels.get_mut(&price).filter(|level| level.is_sparse()) {
+                level.compact(&mut self.index);
+            }
+            let after = self.level_state(side, price);
+            let action = match (before, after) {
+                (None, Some(_)) => DeltaAction::Add,
+                (Some(_), None) => DeltaAction::Delete,
+                (Some(before), Some(after)) if before != after => DeltaAction::Update,
+                _ => continue,
+            };
+            self.sequence += 1;
+            self.deltas.push(BookDelta {
+                sequence: self.sequence,
+                side,
+                action,
+                level: after.unwrap_or(DepthLevel {
+                    price,
+                    quantity: Decimal::ZERO,
+                    order_count: 0,
+                }),
+            });
+        }
+    }
+
+    /// Price levels of one side, best price first.
+    fn levels(&self, side: Side) -> Box<dyn Iterator<Item = (&Decimal, &PriceLevel)> + '_> {
+        match side {
+            Side::Bid => Box::new(self.bids.iter().rev()),
+            Side::Ask => Box::new(self.asks.iter()),
+        }
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Decimal, PriceLevel> {
+        match side {
+            Side::Bid => &mut self.bids,
+            Side::Ask => &mut self.asks,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use sha2::{Digest, Sha256};
+
+use crate::error::OrderError;
+use crate::order::{Order, OrderType, Side, TimeInForce};
+use crate::report::{ExecutionReport, ReportKind};
+
+/// A commitment waiting for its reveal or its round to close.
+#[derive(Debug, Clone)]
+struct Sealed {
+    round: u64,
+    revealed: Option<Revealed>,
+}
+
+#[derive(Debug, Clone)]
+struct Revealed {
+    order: Order,
+    salt: Vec<u8>,
+}
+
+/// Two-phase order entry in front of `OrderBook::add_order`, so nobody can
+/// see an order and trade ahead of it before it is fixed.
+///
+/// Book time is split into rounds: a commit phase, then a reveal phase.
+/// Traders first submit only `commitment(order, salt)`, during the commit
+/// phase. They then submit the order and salt themselves, during the
+/// round's reveal phase. Once the round has closed,
+/// `release` hands its revealed orders over in a sequence drawn from every
+/// revealed salt, so neither commit speed nor any one trader decides who
+/// goes first.
+///
+/// Rejected reveals are returned as errors and also reported as
+/// `ReportKind::Rejected` on `drain_reports`. Commitments that are never
+/// revealed are dropped; a reveal up to one round past its deadline is
+/// still reported as late, after that it matches nothing.
+#[derive(Debug, Clone)]
+pub struct CommitRevealGate {
+    commit_window: u64,
+    reveal_window: u64,
+    commitments: BTreeMap<[u8; 32], Sealed>,
+    reports: Vec<ExecutionReport>,  // Waiting for `drain_reports`
+}
+
+impl CommitRevealGate {
+    /// Creates a gate whose rounds start at book time 0 and take
+    /// `commit_window` then `reveal_window` time units, at least one each.
+    pub fn new(commit_window: u64, reveal_window: u64) -> Self {
+        CommitRevealGate {
+            commit_window: commit_window.max(1),
+            reveal_window: reveal_window.max(1),
+            commitments: BTreeMap::new(),
+            reports: Vec::new(),
+        }
+    }
+
+    pub fn commit_window(&self) -> u64 {
+        self.commit_window
+    }
+
+    pub fn reveal_window(&self) -> u64 {
+        self.reveal_window
+    }
+
+    /// SHA-256 of the order's terms and `salt`. The timestamp is left out:
+    /// released orders are stamped with the time their round closed.
+    pub fn commitment(order: &Order, salt: &[u8]) -> [u8; 32] {
+        let mut input = Vec::new();
+        push_field(&mut input, order.id.as_bytes());
+        let order_type = match order.order_type {
+            OrderType::Limit => 0,
+            OrderType::Market => 1,
+            OrderType::Stop => 2,
+            OrderType::StopLimit => 3,
+        };
+        let side = match order.side {
+            Side::Bid => 0,
+            Side::Ask => 1,
+        };
+        push_field(&mut input, &[order_type, side]);
+        for value in [Some(order.quantity), order.price, order.stop_price, order.display_quantity] {
+            push_optional(&mut input, value.map(|value| value.normalize().to_string()).as_deref());
+        }
+        push_optional(&mut input, order.account.as_deref());
+        let time_in_force = match order.time_in_force {
+            TimeInForce::Gtc => vec![0],
+            TimeInForce::Ioc => vec![1],
+            TimeInForce::Fok => vec![2],
+            TimeInForce::PostOnly => vec![3],
+            TimeInForce::PostOnlySlide => vec![4],
+            TimeInForce::Gtt(expiry) => [&[5], &expiry.to_be_bytes()[..]].concat(),
+        };
+        push_field(&mut input, &time_in_force);
+        push_field(&mut input, salt);
+        Sha256::digest(&input).into()
+    }
+
+    /// Records a commitment made at book time `now`, which must fall in a
+    /// commit phase.
+    pub fn commit(&mut self, commitment: [u8; 32], now: u64) -> Result<(), OrderError> {
+        if now % self.round_length() >= self.commit_window {
+            return Err(OrderError::CommitPhaseClosed);
+        }
+        if self.commitments.contains_key(&commitment) {
+            return Err(OrderError::DuplicateCommitment);
+        }
+        let round = now / self.round_length();
+        self.commitments.insert(commitment, Sealed { round, revealed: None });
+        Ok(())
+    }
+
+    /// Opens the commitment `order` and `salt` hash to, at book time `now`,
+    /// which must fall in the commitment's reveal phase. The order is held
+    /// until its round is released.
+    pub fn reveal(&mut self, order: Order, salt: &[u8], now: u64) -> Result<(), OrderError> {
+        let commitment = Self::commitment(&order, salt);
+        let length = self.round_length();
+        let result = match self.commitments.get_mut(&commitment) {
+            None => Err(OrderError::CommitmentMismatch(order.id.clone())),
+            Some(sealed) if now >= (sealed.round + 1) * length => Err(OrderError::LateReveal(order.id.clone())),
+            Some(_) if now % length < self.commit_window => Err(OrderError::EarlyReveal(order.id.clone())),
+            Some(sealed) if sealed.revealed.is_some() => Err(OrderError::DuplicateOrderId(order.id.clone())),
+            Some(sealed) => {
+                sealed.revealed = Some(Revealed { order: order.clone(), salt: salt.to_vec() });
+                Ok(())
+            }
+        };
+        if let Err(error) = &result {
+            self.reports.push(ExecutionReport::new(&order, ReportKind::Rejected(error.clone())));
+        }
+        result
+    }
+
+    /// Takes the revealed orders of every round closed by book time `now`,
+    /// earliest round first, for the caller to submit to the book in the
+    /// sequence given.
+    pub fn release(&mut self, now: u64) -> Vec<Order> {
+        let length = self.round_length();
+        let mut rounds: BTreeMap<u64, Vec<([u8; 32], Revealed)>> = BTreeMap::new();
+        self.commitments.retain(|commitment, sealed| {
+            if (sealed.round + 1) * length > now {
+                return true;
+            }
+            match sealed.revealed.take() {
+                Some(revealed) => {
+                    rounds.entry(sealed.round).or_default().push((*commitment, revealed));
+                    false
+                }
+                None => (sealed.round + 2) * length > now,  // Kept a round longer to report late reveals
+            }
+        });
+
+        let mut released = Vec::new();
+        for (round, mut revealed) in rounds {
+            // Every salt goes into the seed, so it is unknown until the last reveal
+            let mut seed = Vec::new();
+            for (commitment, revealed) in &revealed {
+                push_field(&mut seed, commitment);
+                push_field(&mut seed, &revealed.salt);
+            }
+            let seed: [u8; 32] = Sha256::digest(&seed).into();
+            revealed.sort_by_cached_key(|(commitment, _)| Sha256::digest([seed, *commitment].concat()));
+
+            let close = (round + 1) * length;
+            released.extend(revealed.into_iter().map(|(_, revealed)| Order {
+                timestamp: close,
+                ..revealed.order
+            }));
+        }
+        released
+    }
+
+    /// Takes the reports for rejected reveals since the last call, oldest
+    /// first.
+    pub fn drain_reports(&mut self) -> Vec<ExecutionReport> {
+        std::mem::take(&mut self.reports)
+    }
+
+    fn round_length(&self) -> u64 {
+        self.commit_window + self.reveal_window
+    }
+}
+
+/// Appends `field` with its length, so adjacent fields can't run together.
+fn push_field(input: &mut Vec<u8>, field: &[u8]) {
+    input.extend_from_slice(&(field.len() as u64).to_be_bytes());
+    input.extend_from_slice(field);
+}
+
+fn push_optional(input: &mut Vec<u8>, field: Option<&str>) {
+    match field {
+        Some(field) => push_field(input, &[&[1], field.as_bytes()].concat()),
+        None => push_field(input, &[0]),
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashSet;
+use rust_decimal::Decimal;
+
+use crate::book::OrderBook;
+use crate::error::OrderError;
+use crate::instrument::InstrumentSpec;
+use crate::order::{Order, OrderType, Side, TimeInForce};
+use crate::report::{ExecutionReport, ReportKind};
+use crate::trade::{Execution, Trade};
+
+/// An order for a `DarkPool`: pegged to the lit midpoint, with its limit
+/// price, if any, as a cap (bids) or floor (asks) on the midpoint it
+/// accepts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DarkOrder {
+    pub(crate) order: Order,
+    pub(crate) min_quantity: Decimal,  // Smallest fill it accepts; zero takes any
+    pub(crate) conditional: bool,      // Trades only once firmed up
+}
+
+impl DarkOrder {
+    /// Wraps a limit order, or a market order to peg at any midpoint.
+    pub fn new(order: Order) -> Self {
+        DarkOrder {
+            order,
+            min_quantity: Decimal::ZERO,
+            conditional: false,
+        }
+    }
+
+    /// Refuses fills smaller than `min_quantity`, unless they complete the
+    /// order.
+    pub fn with_min_quantity(mut self, min_quantity: Decimal) -> Self {
+        self.min_quantity = min_quantity;
+        self
+    }
+
+    /// Makes the order a conditional: it rests without trading, and the
+    /// pool invites its owner to firm it up when a contra could fill it.
+    pub fn conditional(mut self) -> Self {
+        self.conditional = true;
+        self
+    }
+
+    pub fn order(&self) -> &Order {
+        &self.order
+    }
+
+    pub fn min_quantity(&self) -> Decimal {
+        self.min_quantity
+    }
+
+    pub fn is_conditional(&self) -> bool {
+        self.conditional
+    }
+
+    /// Smallest fill it takes right now.
+    fn min_fill(&self) -> Decimal {
+        self.min_quantity.min(self.order.quantity)
+    }
+
+    /// Whether its limit allows trading at `mid`.
+    fn accepts(&self, mid: Decimal) -> bool {
+        match (self.order.side, self.order.price) {
+            (_, None) => true,
+            (Side::Bid, Some(limit)) => mid <= limit,
+            (Side::Ask, Some(limit)) => mid >= limit,
+        }
+    }
+}
+
+/// Tells the owner of a conditional order that it could trade now.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirmUpInvitation {
+    pub(crate) order_id: String,
+    pub(crate) price: Decimal,     // Midpoint when the contra was found
+    pub(crate) quantity: Decimal,  // What would have traded
+}
+
+impl FirmUpInvitation {
+    pub fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    pub fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+}
+
+/// A non-displayed crossing book run alongside a lit `OrderBook`. Its
+/// orders never appear in depth or deltas, and they trade with each other
+/// only at the lit book's midpoint, read from its best bid and ask when the
+/// pool is called. With a one-sided or empty lit book nothing trades.
+///
+/// Orders match in time priority, skipping contras whose limit rejects the
+/// midpoint, whose minimum fill isn't met, or that belong to the same
+/// account. The resting order is the maker. As the lit midpoint moves,
+/// `cross` retries resting orders against each other, the older one making.
+/// Trades take their ids from the lit book, so the two never reuse an id.
+///
+/// Conditional orders never trade as they stand. When one meets a contra
+/// it could trade with, its owner receives a `FirmUpInvitation`, once until
+/// the order is firmed up or gone; `firm_up` turns it into a firm order in
+/// place and matches it.
+#[derive(Debug, Clone, Default)]
+pub struct DarkPool {
+    orders: Vec<DarkOrder>,  // Resting, in time priority
+    invited: HashSet<String>,
+    invitations: Vec<FirmUpInvitation>,  // Waiting for `drain_invitations`
+    reports: Vec<ExecutionReport>,       // Waiting for `drain_reports`
+    sequence: u64,  // Counts calls that can change the pool
+    clock: u64,     // Latest order timestamp seen
+}
+
+impl DarkPool {
+    pub fn new() -> Self {
+        DarkPool::default()
+    }
+
+    pub fn order(&self, id: &str) -> Option<&DarkOrder> {
+        self.orders.iter().find(|order| order.order.id == id)
+    }
+
+    /// Resting orders, in time priority.
+    pub fn orders(&self) -> &[DarkOrder] {
+        &self.orders
+    }
+
+    /// Submits an order and matches it at `lit`'s midpoint. The order is
+    /// held to the lit instrument's lot, order size and notional rules.
+    /// Good-till-cancelled orders rest, market orders included; immediate-
+    /// or-cancel orders cancel what doesn't trade. Conditional orders
+    /// always rest.
+    pub fn add_order(&mut self, order: DarkOrder, lit: &mut OrderBook) -> Result<Execution, OrderError> {
+        self.sequence += 1;
+        if let Err(error) = self.validate(&order, lit.spec()) {
+            self.reports.push(ExecutionReport::new(&order.order, ReportKind::Rejected(error.clone())));
+            return Err(error);
+        }
+        self.clock = self.clock.max(order.order.timestamp);
+
+        let mut execution = Execution::default();
+        execution.reports.push(ExecutionReport::new(&order.order, ReportKind::Accepted));
+        let mut order = order;
+        if let Some(mid) = lit.mid() {
+            self.match_order(&mut order, mid, &mut execution);
+        }
+        execution.filled_quantity = execution.trades.iter().map(|trade| trade.quantity).sum();
+        if order.order.quantity > Decimal::ZERO {
+            if order.order.time_in_force == TimeInForce::Gtc {
+                execution.resting_quantity = order.order.quantity;
+                self.orders.push(order);
+            } else {
+                execution.cancelled_quantity = order.order.quantity;
+                execution.reports.push(ExecutionReport::new(&order.order, ReportKind::Cancelled));
+            }
+        }
+        self.stamp_trades(&mut execution, lit);
+        Ok(execution)
+    }
+
+    /// Removes a resting order.
+    pub fn cancel_order(&mut self, id: &str) -> Result<DarkOrder, OrderError> {
+        self.sequence += 1;
+        let position = self.position(id)?;
+        let order = self.orders.remove(position);
+        self.invited.remove(id);
+        self.reports.push(ExecutionReport::new(&order.order, ReportKind::Cancelled));
+        Ok(order)
+    }
+
+    /// Turns a conditional order into a firm one, keeping its time
+    /// priority, and matches it at `lit`'s midpoint. Whatever doesn't trade
+    /// keeps resting as a firm order.
+    pub fn firm_up(&mut self, id: &str, lit: &mut OrderBook) -> Result<Execution, OrderError> {
+        self.sequence += 1;
+        let position = self.position(id)?;
+        let ahead: HashSet<String> = self.orders[..position].iter().map(|order| order.order.id.clone()).collect();
+        let mut order = self.orders.remove(position);
+        order.conditional = false;
+        self.invited.remove(id);
+
+        let mut execution = Execution::default();
+        if let Some(mid) = lit.mid() {
+            self.match_order(&mut order, mid, &mut execution);
+        }
+        execution.filled_quantity = execution.trades.iter().map(|trade| trade.quantity).sum();
+        if order.order.quantity > Decimal::ZERO {
+            execution.resting_quantity = order.order.quantity;
+            // Back behind whatever is left of the orders that were ahead of it
+            let position = self.orders.iter().filter(|order| ahead.contains(&order.order.id)).count();
+            self.orders.insert(position, order);
+        }
+        self.stamp_trades(&mut execution, lit);
+        Ok(execution)
+    }
+
+    /// Retries every resting order against older ones at `lit`'s current
+    /// midpoint, for when the lit market has moved.
+    pub fn cross(&mut self, lit: &mut OrderBook) -> Execution {
+        self.sequence += 1;
+        let mut execution = Execution::default();
+        if let Some(mid) = lit.mid() {
+            for mut order in std::mem::take(&mut self.orders) {
+                self.match_order(&mut order, mid, &mut execution);
+                if order.order.quantity > Decimal::ZERO {
+                    self.orders.push(order);
+                }
+            }
+        }
+        self.stamp_trades(&mut execution, lit);
+        execution
+    }
+
+    /// Hands over the execution reports emitted since the last call, oldest
+    /// first.
+    pub fn drain_reports(&mut self) -> Vec<ExecutionReport> {
+        std::mem::take(&mut self.reports)
+    }
+
+    /// Hands over the firm-up invitations raised since the last call,
+    /// oldest first.
+    pub fn drain_invitations(&mut self) -> Vec<FirmUpInvitation> {
+        std::mem::take(&mut self.invitations)
+    }
+
+    /// Checks `order` against the pool's own rules and the size rules of
+    /// the lit instrument. Limit prices only bound the midpoint, so they
+    /// need not be on tick.
+    fn validate(&self, order: &DarkOrder, spec: &InstrumentSpec) -> Result<(), OrderError> {
+        let inner = &order.order;
+        if !matches!(inner.order_type, OrderType::Limit | OrderType::Market) {
+            return Err(OrderError::UnsupportedOrderType(inner.order_type));
+        }
+        spec.validate_quantity(inner.quantity)?;
+        if order.min_quantity < Decimal::ZERO {
+            return Err(OrderError::InvalidQuantity(order.min_quantity));
+        }
+        if order.min_quantity > Decimal::ZERO {
+            spec.validate_lot(order.min_quantity)?;
+        }
+        if let Some(price) = inner.price {
+            if price <= Decimal::ZERO {
+                return Err(OrderError::InvalidPrice(price));
+            }
+            spec.validate_notional(price, inner.quantity)?;
+        }
+        let supported = match inner.time_in_force {
+            TimeInForce::Gtc => true,
+            TimeInForce::Ioc => !order.conditional,  // A conditional has to rest to be invited
+            _ => false,
+        };
+        if !supported {
+            return Err(OrderError::InvalidTimeInForce(inner.time_in_force));
+        }
+        if self.order(&inner.id).is_some() {
+            return Err(OrderError::DuplicateOrderId(inner.id.clone()));
+        }
+        Ok(())
+    }
+
+    fn position(&self, id: &str) -> Result<usize, OrderError> {
+        self.orders
+            .iter()
+            .position(|order| order.order.id == id)
+            .ok_or_else(|| OrderError::OrderNotFound(id.to_string()))
+    }
+
+    /// Trades `taker` against resting orders at `mid`, in time priority.
+    /// Where either side is conditional, invitations go out instead.
+    fn match_order(&mut self, taker: &mut DarkOrder, mid: Decimal, execution: &mut Execution) {
+        if !taker.accepts(mid) {
+            return;
+        }
+        let mut position = 0;
+        while position < self.orders.len() && taker.order.quantity > Decimal::ZERO {
+            let maker = &mut self.orders[position];
+            let quantity = maker.order.quantity.min(taker.order.quantity);
+            let same_account = maker.order.account.is_some() && maker.order.account == taker.order.account;
+            if maker.order.side == taker.order.side
+                || same_account
+                || !maker.accepts(mid)
+                || quantity < maker.min_fill()
+                || quantity < taker.min_fill()
+            {
+                position += 1;
+                continue;
+            }
+            if maker.conditional || taker.conditional {
+                let maker_id = maker.conditional.then(|| maker.order.id.clone());
+                let taker_id = taker.conditional.then(|| taker.order.id.clone());
+                for id in maker_id.into_iter().chain(taker_id) {
+                    self.invite(id, mid, quantity);
+                }
+                position += 1;
+                continue;
+            }
+
+            maker.order.fill(quantity);
+            taker.order.fill(quantity);
+            // Id, timestamp and sequence are stamped by the pool
+            execution.trades.push(Trade {
+                id: 0,
+                maker_order_id: maker.order.id.clone(),
+                taker_order_id: taker.order.id.clone(),
+                maker_account: maker.order.account.clone(),
+                taker_account: taker.order.account.clone(),
+                aggressor: taker.order.side,
+                price: mid,
+                quantity,
+                maker_remaining: maker.order.quantity,
+                taker_remaining: taker.order.quantity,
+                timestamp: 0,
+                sequence: 0,
+                settled: false,
+            });
+            execution.reports.push(ExecutionReport::fill(&maker.order, mid, quantity));
+            execution.reports.push(ExecutionReport::fill(&taker.order, mid, quantity));
+            if maker.order.quantity.is_zero() {
+                self.orders.remove(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+
+    fn invite(&mut self, order_id: String, price: Decimal, quantity: Decimal) {
+        if self.invited.insert(order_id.clone()) {
+            self.invitations.push(FirmUpInvitation { order_id, price, quantity });
+        }
+    }
+
+    /// Numbers the trades of a call from `lit`'s trade ids and moves its
+    /// reports to the queue.
+    fn stamp_trades(&mut self, execution: &mut Execution, lit: &mut OrderBook) {
+        for trade in &mut execution.trades {
+            trade.id = lit.next_trade_id();
+            trade.timestamp = self.clock;
+            trade.sequence = self.sequence;
+        }
+        self.reports.append(&mut execution.reports);
+    }
+}
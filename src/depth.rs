@@ -0,0 +1,116 @@
+use rust_decimal::Decimal;
+
+use crate::level::PriceLevel;
+use crate::order::Side;
+
+/// Aggregated view of one price level: displayed quantity and the number of
+/// orders behind it. Hidden iceberg reserve is not included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthLevel {
+    pub(crate) price: Decimal,
+    pub(crate) quantity: Decimal,
+    pub(crate) order_count: usize,
+}
+
+impl DepthLevel {
+    pub(crate) fn from_level(price: Decimal, level: &PriceLevel) -> Self {
+        DepthLevel {
+            price,
+            quantity: level.visible_quantity(),
+            order_count: level.order_count(),
+        }
+    }
+
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    pub fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+
+    pub fn order_count(&self) -> usize {
+        self.order_count
+    }
+}
+
+/// Level-2 view of both sides of the book, best price first.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Depth {
+    pub(crate) bids: Vec<DepthLevel>,
+    pub(crate) asks: Vec<DepthLevel>,
+}
+
+impl Depth {
+    pub fn bids(&self) -> &[DepthLevel] {
+        &self.bids
+    }
+
+    pub fn asks(&self) -> &[DepthLevel] {
+        &self.asks
+    }
+
+    /// Turns per-level figures into running totals from the touch outwards.
+    pub(crate) fn accumulate(mut self) -> Self {
+        for levels in [&mut self.bids, &mut self.asks] {
+            let mut quantity = Decimal::ZERO;
+            let mut order_count = 0;
+            for level in levels.iter_mut() {
+                quantity += level.quantity;
+                order_count += level.order_count;
+                level.quantity = quantity;
+                level.order_count = order_count;
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeltaAction {
+    Add,     // A new price level appeared
+    Update,  // An existing level's displayed quantity or order count changed
+    Delete,  // The level is gone
+}
+
+/// One price level change in the incremental market-data feed.
+///
+/// Sequence numbers start at 1 and increase by one per delta, so a consumer
+/// that sees a gap knows it missed updates. To resync, apply a
+/// `BookSnapshot` and then every delta with a higher sequence than the
+/// snapshot's.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BookDelta {
+    pub(crate) sequence: u64,
+    pub(crate) side: Side,
+    pub(crate) action: DeltaAction,
+    pub(crate) level: DepthLevel,  // State after the change; zeroed for deletes
+}
+
+impl BookDelta {
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn action(&self) -> DeltaAction {
+        self.action
+    }
+
+    pub fn price(&self) -> Decimal {
+        self.level.price
+    }
+
+    /// Displayed quantity at the level after the change.
+    pub fn quantity(&self) -> Decimal {
+        self.level.quantity
+    }
+
+    /// Orders at the level after the change.
+    pub fn order_count(&self) -> usize {
+        self.level.order_count
+    }
+}
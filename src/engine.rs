@@ -0,0 +1,425 @@
+use std::collections::{BTreeMap, HashMap};
+use rust_decimal::Decimal;
+
+use crate::account::{self, AccountLedger, Reservation};
+use crate::auction::MarketMode;
+use crate::book::OrderBook;
+use crate::depth::{BookDelta, DepthLevel};
+use crate::error::OrderError;
+use crate::instrument::{self, InstrumentSpec};
+use crate::order::{Order, Side};
+use crate::report::ExecutionReport;
+use crate::trade::Execution;
+
+/// Whether a symbol currently takes orders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TradingStatus {
+    #[default]
+    Trading,     // Orders, amends and cancels are accepted
+    CancelOnly,  // Only cancels are accepted, resting orders stay on the book
+    Halted,      // Nothing is accepted; the book is frozen as it is
+}
+
+impl TradingStatus {
+    pub fn accepts_orders(self) -> bool {
+        self == TradingStatus::Trading
+    }
+
+    pub fn accepts_cancels(self) -> bool {
+        self != TradingStatus::Halted
+    }
+}
+
+struct Listing {
+    book: OrderBook,
+    status: TradingStatus,
+}
+
+/// Owns one `OrderBook` per instrument and routes requests to them by symbol.
+///
+/// Instruments whose spec names a base and quote asset are funded: every
+/// order must carry an account and reserves what it could pay from that
+/// account's balance before it reaches the book. Fills pay out of the
+/// reservation, and cancels and expiries release what is left of it. The
+/// engine is the only settlement path for those trades: they come back
+/// marked settled, and a `SettlementBatch` refuses them.
+#[derive(Default)]
+pub struct MatchingEngine {
+    listings: BTreeMap<String, Listing>,  // Books by symbol, iterated in symbol order
+    ledger: AccountLedger,
+    reservations: HashMap<(String, String), Reservation>,  // By (symbol, order id)
+}
+
+impl MatchingEngine {
+    pub fn new() -> Self {
+        MatchingEngine::default()
+    }
+
+    /// Lists a new instrument with an empty book validating against `spec`.
+    pub fn add_instrument(
+        &mut self,
+        symbol: impl Into<String>,
+        spec: InstrumentSpec,
+    ) -> Result<(), OrderError> {
+        self.add_book(symbol, OrderBook::with_spec(spec)?)
+    }
+
+    /// Lists a new instrument with an already configured book, e.g. one
+    /// restored from a snapshot or using a non-default matching policy.
+    ///
+    /// Orders already on the book hold no reservations, so on a funded
+    /// instrument their fills only move funds for the counterparty.
+    pub fn add_book(&mut self, symbol: impl Into<String>, book: OrderBook) -> Result<(), OrderError> {
+        let symbol = symbol.into();
+        if self.listings.contains_key(&symbol) {
+            return Err(OrderError::DuplicateSymbol(symbol));
+        }
+        let listing = Listing {
+            book,
+            status: TradingStatus::Trading,
+        };
+        self.listings.insert(symbol, listing);
+        Ok(())
+    }
+
+    /// Delists an instrument and hands back its book with any orders still
+    /// on it. Their reservations are released.
+    pub fn remove_instrument(&mut self, symbol: &str) -> Result<OrderBook, OrderError> {
+        let listing = self
+            .listings
+            .remove(symbol)
+            .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))?;
+        let ledger = &mut self.ledger;
+        self.reservations.retain(|(listed, _), reservation| {
+            if listed != symbol {
+                return true;
+            }
+            ledger.release(&reservation.account, &reservation.asset, reservation.amount);
+            false
+        });
+        Ok(listing.book)
+    }
+
+    /// Listed symbols in ascending order.
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.listings.keys().map(String::as_str)
+    }
+
+    pub fn book(&self, symbol: &str) -> Option<&OrderBook> {
+        self.listings.get(symbol).map(|listing| &listing.book)
+    }
+
+    /// Direct access to a book. Requests made through it bypass trading
+    /// status and funding.
+    pub fn book_mut(&mut self, symbol: &str) -> Option<&mut OrderBook> {
+        self.listings.get_mut(symbol).map(|listing| &mut listing.book)
+    }
+
+    pub fn ledger(&self) -> &AccountLedger {
+        &self.ledger
+    }
+
+    /// The ledger funding orders, for deposits and withdrawals.
+    pub fn ledger_mut(&mut self) -> &mut AccountLedger {
+        &mut self.ledger
+    }
+
+    pub fn trading_status(&self, symbol: &str) -> Option<TradingStatus> {
+        self.listings.get(symbol).map(|listing| listing.status)
+    }
+
+    pub fn set_trading_status(
+        &mut self,
+        symbol: &str,
+        status: TradingStatus,
+    ) -> Result<(), OrderError> {
+        self.listings
+            .get_mut(symbol)
+            .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))?
+            .status = status;
+        Ok(())
+    }
+
+    /// Submits `order` to the book listed under `symbol`. On a funded
+    /// instrument the order's funds are reserved first and it is rejected if
+    /// its account can't cover them; bids there need a limit price. Orders
+    /// the engine rejects are reported through the book like any other.
+    pub fn add_order(&mut self, symbol: &str, order: Order) -> Result<Execution, OrderError> {
+        let listing = self
+            .listings
+            .get_mut(symbol)
+            .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))?;
+        let reservation = match reserve(&mut self.ledger, symbol, listing, &order) {
+            Ok(reservation) => reservation,
+            Err(error) => {
+                listing.book.reject(&order, error.clone());
+                return Err(error);
+            }
+        };
+        let Some(reservation) = reservation else {
+            return listing.book.add_order(order);
+        };
+
+        let id = order.id.clone();
+        let mut execution = match listing.book.add_order(order) {
+            Ok(execution) => execution,
+            Err(error) => {
+                self.ledger.release(&reservation.account, &reservation.asset, reservation.amount);
+                return Err(error);
+            }
+        };
+        self.reservations.insert((symbol.to_string(), id.clone()), reservation);
+        settle(&mut self.ledger, &mut self.reservations, symbol, &listing.book, &mut execution, Some(&id));
+        Ok(execution)
+    }
+
+    /// Cancels an order and releases whatever it had reserved.
+    pub fn cancel_order(&mut self, symbol: &str, id: &str) -> Result<Order, OrderError> {
+        let listing = tradable(&mut self.listings, symbol, TradingStatus::accepts_cancels)?;
+        let order = listing.book.cancel_order(id)?;
+        release(&mut self.ledger, &mut self.reservations, symbol, &listing.book, id);
+        Ok(order)
+    }
+
+    /// Amends an order. On a funded instrument any extra funds the new price
+    /// or quantity needs are reserved first.
+    pub fn amend_order(
+        &mut self,
+        symbol: &str,
+        id: &str,
+        new_price: Decimal,
+        new_quantity: Decimal,
+    ) -> Result<Execution, OrderError> {
+        let listing = self
+            .listings
+            .get_mut(symbol)
+            .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))?;
+        let key = (symbol.to_string(), id.to_string());
+        let funded = admit(symbol, listing, TradingStatus::accepts_orders).and_then(|()| {
+            let (Some(reservation), Some(order)) = (self.reservations.get_mut(&key), listing.book.order(id)) else {
+                return Ok(());
+            };
+            let required = match order.side {
+                Side::Bid => instrument::notional(new_price, new_quantity)?,
+                Side::Ask => new_quantity,
+            };
+            if required > reservation.amount {
+                let extra = required - reservation.amount;
+                self.ledger.reserve(&reservation.account, &reservation.asset, extra)?;
+                reservation.amount = required;
+            }
+            Ok(())
+        });
+        if let Err(error) = funded {
+            listing.book.reject_amend(id, error.clone());
+            return Err(error);
+        }
+
+        let mut result = listing.book.amend_order(id, new_price, new_quantity);
+        match &mut result {
+            Ok(execution) => {
+                settle(&mut self.ledger, &mut self.reservations, symbol, &listing.book, execution, Some(id))
+            }
+            Err(_) => release(&mut self.ledger, &mut self.reservations, symbol, &listing.book, id),
+        }
+        result
+    }
+
+    /// Expires good-till-time orders on every book that isn't halted, and
+    /// clears any batch auction that closed by `now`.
+    pub fn expire_orders(&mut self, now: u64) -> Vec<(String, Order)> {
+        let mut expired = Vec::new();
+        for (symbol, listing) in &mut self.listings {
+            if !listing.status.accepts_cancels() {
+                continue;
+            }
+            let mut execution = listing.book.run_auction(now);
+            settle(&mut self.ledger, &mut self.reservations, symbol, &listing.book, &mut execution, None);
+            expired.extend(execution.expired_orders.into_iter().map(|order| (symbol.clone(), order)));
+        }
+        expired
+    }
+
+    /// Advances one book's clock to `now`, clearing its batch auction if it
+    /// closed by then, and settles the trades on a funded instrument.
+    pub fn run_auction(&mut self, symbol: &str, now: u64) -> Result<Execution, OrderError> {
+        let listing = tradable(&mut self.listings, symbol, TradingStatus::accepts_orders)?;
+        let mut execution = listing.book.run_auction(now);
+        settle(&mut self.ledger, &mut self.reservations, symbol, &listing.book, &mut execution, None);
+        Ok(execution)
+    }
+
+    /// Switches a book between continuous matching and batch auctions. The
+    /// auction cleared on leaving batch mode settles like any other.
+    pub fn set_market_mode(&mut self, symbol: &str, mode: MarketMode) -> Result<Execution, OrderError> {
+        let listing = tradable(&mut self.listings, symbol, TradingStatus::accepts_orders)?;
+        let mut execution = listing.book.set_market_mode(mode);
+        settle(&mut self.ledger, &mut self.reservations, symbol, &listing.book, &mut execution, None);
+        Ok(execution)
+    }
+
+    /// Finds a resting or stop order by id across every book.
+    pub fn find_order(&self, id: &str) -> Option<(&str, &Order)> {
+        self.listings
+            .iter()
+            .find_map(|(symbol, listing)| Some((symbol.as_str(), listing.book.order(id)?)))
+    }
+
+    /// Best bid and ask of every book, in symbol order.
+    pub fn top_of_book(
+        &self,
+    ) -> impl Iterator<Item = (&str, Option<DepthLevel>, Option<DepthLevel>)> {
+        self.listings
+            .iter()
+            .map(|(symbol, listing)| (symbol.as_str(), listing.book.best_bid(), listing.book.best_ask()))
+    }
+
+    /// Last traded price of every book that has traded, in symbol order.
+    pub fn last_prices(&self) -> impl Iterator<Item = (&str, Decimal)> {
+        self.listings
+            .iter()
+            .filter_map(|(symbol, listing)| Some((symbol.as_str(), listing.book.last_price()?)))
+    }
+
+    /// Takes the queued execution reports of every book, tagged with their
+    /// symbol.
+    pub fn drain_reports(&mut self) -> Vec<(String, ExecutionReport)> {
+        let mut reports = Vec::new();
+        for (symbol, listing) in &mut self.listings {
+            let drained = listing.book.drain_reports();
+            reports.extend(drained.into_iter().map(|report| (symbol.clone(), report)));
+        }
+        reports
+    }
+
+    /// Takes the queued level deltas of every book, tagged with their symbol.
+    /// Each book keeps its own delta sequence.
+    pub fn drain_deltas(&mut self) -> Vec<(String, BookDelta)> {
+        let mut deltas = Vec::new();
+        for (symbol, listing) in &mut self.listings {
+            let drained = listing.book.drain_deltas();
+            deltas.extend(drained.into_iter().map(|delta| (symbol.clone(), delta)));
+        }
+        deltas
+    }
+}
+
+/// The listing under `symbol`, if its status lets the request through.
+fn tradable<'a>(
+    listings: &'a mut BTreeMap<String, Listing>,
+    symbol: &str,
+    allowed: fn(TradingStatus) -> bool,
+) -> Result<&'a mut Listing, OrderError> {
+    let listing = listings
+        .get_mut(symbol)
+        .ok_or_else(|| OrderError::UnknownSymbol(symbol.to_string()))?;
+    admit(symbol, listing, allowed)?;
+    Ok(listing)
+}
+
+/// Refuses a request that `listing`'s trading status doesn't let through.
+fn admit(symbol: &str, listing: &Listing, allowed: fn(TradingStatus) -> bool) -> Result<(), OrderError> {
+    if !allowed(listing.status) {
+        return Err(OrderError::SymbolNotTrading {
+            symbol: symbol.to_string(),
+            status: listing.status,
+        });
+    }
+    Ok(())
+}
+
+/// Checks `listing` takes orders and, on a funded instrument, reserves what
+/// `order` could pay. Returns `None` when the instrument isn't funded.
+fn reserve(
+    ledger: &mut AccountLedger,
+    symbol: &str,
+    listing: &Listing,
+    order: &Order,
+) -> Result<Option<Reservation>, OrderError> {
+    admit(symbol, listing, TradingStatus::accepts_orders)?;
+    let Some(asset) = listing.book.spec().funding_asset(order.side) else {
+        return Ok(None);
+    };
+    let account = order
+        .account
+        .clone()
+        .ok_or_else(|| OrderError::MissingAccount(order.id.clone()))?;
+    let amount = account::required_funds(order)?;
+    ledger.reserve(&account, asset, amount)?;
+    Ok(Some(Reservation {
+        account,
+        asset: asset.to_string(),
+        amount,
+    }))
+}
+
+/// Pays every trade in `execution` out of the reservations of the orders
+/// involved and marks it settled, then trims those reservations to what the
+/// orders still need.
+fn settle(
+    ledger: &mut AccountLedger,
+    reservations: &mut HashMap<(String, String), Reservation>,
+    symbol: &str,
+    book: &OrderBook,
+    execution: &mut Execution,
+    submitted_id: Option<&str>,
+) {
+    let (Some(base), Some(quote)) = (book.spec().base_asset(), book.spec().quote_asset()) else {
+        return;
+    };
+    for trade in &mut execution.trades {
+        trade.settled = true;
+        let (buyer, seller) = match trade.aggressor() {
+            Side::Bid => (trade.taker_order_id(), trade.maker_order_id()),
+            Side::Ask => (trade.maker_order_id(), trade.taker_order_id()),
+        };
+        let cost = trade.price() * trade.quantity();
+        if let Some(reservation) = reservations.get_mut(&(symbol.to_string(), buyer.to_string())) {
+            ledger.spend_reserved(&reservation.account, quote, cost);
+            ledger.credit(&reservation.account, base, trade.quantity());
+            reservation.amount -= cost;
+        }
+        if let Some(reservation) = reservations.get_mut(&(symbol.to_string(), seller.to_string())) {
+            ledger.spend_reserved(&reservation.account, base, trade.quantity());
+            ledger.credit(&reservation.account, quote, cost);
+            reservation.amount -= trade.quantity();
+        }
+    }
+
+    let touched = submitted_id
+        .into_iter()
+        .chain(execution.trades().iter().flat_map(|trade| [trade.maker_order_id(), trade.taker_order_id()]))
+        .chain(execution.triggered_order_ids().iter().map(String::as_str))
+        .chain(execution.cancelled_order_ids().iter().map(String::as_str))
+        .chain(execution.reduced_order_ids().iter().map(String::as_str))
+        .chain(execution.expired_orders().iter().map(Order::id));
+    for id in touched {
+        release(ledger, reservations, symbol, book, id);
+    }
+}
+
+/// Releases whatever order `id` holds beyond what it still needs, and drops
+/// its reservation once the order is off the book.
+fn release(
+    ledger: &mut AccountLedger,
+    reservations: &mut HashMap<(String, String), Reservation>,
+    symbol: &str,
+    book: &OrderBook,
+    id: &str,
+) {
+    let key = (symbol.to_string(), id.to_string());
+    let Some(reservation) = reservations.get_mut(&key) else {
+        return;
+    };
+    let order = book.order(id);
+    let required = order
+        .map(|order| account::required_funds(order).expect("resting funded bids were costed on entry"))
+        .unwrap_or(Decimal::ZERO);
+    if reservation.amount > required {
+        ledger.release(&reservation.account, &reservation.asset, reservation.amount - required);
+        reservation.amount = required;
+    }
+    if order.is_none() {
+        reservations.remove(&key);
+    }
+}
@@ -0,0 +1,211 @@
+use std::fmt;
+
+use rust_decimal::Decimal;
+
+use crate::engine::TradingStatus;
+use crate::order::{OrderType, TimeInForce};
+
+/// Reasons an order request is refused by the book.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OrderError {
+    InvalidQuantity(Decimal),
+    InvalidPrice(Decimal),
+    DuplicateOrderId(String),
+    OffTickPrice { price: Decimal, tick_size: Decimal },
+    InvalidTickSize(Decimal),
+    InvalidLotSize(Decimal),
+    ExcessPricePrecision { price: Decimal, precision: u32 },
+    OffLotQuantity { quantity: Decimal, lot_size: Decimal },
+    PolicyLotMismatch { policy_lot_size: Decimal, lot_size: Decimal },
+    QuantityBelowMinimum { quantity: Decimal, minimum: Decimal },
+    QuantityAboveMaximum { quantity: Decimal, maximum: Decimal },
+    NotionalBelowMinimum { notional: Decimal, minimum: Decimal },
+    NotionalOverflow { price: Decimal, quantity: Decimal },
+    OrderNotFound(String),
+    InvalidTimeInForce(TimeInForce),
+    UnsupportedOrderType(OrderType),
+    WouldTakeLiquidity,
+    CannotRest(String),
+    UnknownSymbol(String),
+    DuplicateSymbol(String),
+    SymbolNotTrading { symbol: String, status: TradingStatus },
+    MissingAccount(String),
+    UnpricedBid(String),
+    InsufficientFunds { account: String, asset: String, required: Decimal, available: Decimal },
+    TradeAlreadySettled(u64),
+    CommitPhaseClosed,
+    DuplicateCommitment,
+    CommitmentMismatch(String),
+    EarlyReveal(String),
+    LateReveal(String),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrderError::InvalidQuantity(quantity) => {
+                write!(f, "quantity must be positive, got {quantity}")
+            }
+            OrderError::InvalidPrice(price) => write!(f, "price must be positive, got {price}"),
+            OrderError::DuplicateOrderId(id) => write!(f, "order id {id} is already live"),
+            OrderError::OffTickPrice { price, tick_size } => {
+                write!(f, "price {price} is not a multiple of tick size {tick_size}")
+            }
+            OrderError::InvalidTickSize(tick_size) => {
+                write!(f, "tick size must be positive, got {tick_size}")
+            }
+            OrderError::InvalidLotSize(lot_size) => {
+                write!(f, "lot size must be positive, got {lot_size}")
+            }
+            OrderError::ExcessPricePrecision { price, precision } => {
+                write!(f, "price {price} has more than {precision} decimal places")
+            }
+            OrderError::OffLotQuantity { quantity, lot_size } => {
+                write!(f, "quantity {quantity} is not a multiple of lot size {lot_size}")
+            }
+            OrderError::PolicyLotMismatch { policy_lot_size, lot_size } => write!(
+                f,
+                "matching policy lots of {policy_lot_size} are not whole lots of {lot_size}"
+            ),
+            OrderError::QuantityBelowMinimum { quantity, minimum } => {
+                write!(f, "quantity {quantity} is below the minimum of {minimum}")
+            }
+            OrderError::QuantityAboveMaximum { quantity, maximum } => {
+                write!(f, "quantity {quantity} is above the maximum of {maximum}")
+            }
+            OrderError::NotionalBelowMinimum { notional, minimum } => {
+                write!(f, "notional {notional} is below the minimum of {minimum}")
+            }
+            OrderError::NotionalOverflow { price, quantity } => {
+                write!(f, "notional of {quantity} at {price} is too large to represent")
+            }
+            OrderError::OrderNotFound(id) => write!(f, "no resting order with id {id}"),
+            OrderError::InvalidTimeInForce(time_in_force) => {
+                write!(f, "time in force {time_in_force:?} is not valid for this order")
+            }
+            OrderError::UnsupportedOrderType(order_type) => {
+                write!(f, "{order_type:?} orders are not accepted in this market mode")
+            }
+            OrderError::WouldTakeLiquidity => write!(f, "post-only order would take liquidity"),
+            OrderError::CannotRest(id) => write!(f, "order {id} cannot rest on the book"),
+            OrderError::UnknownSymbol(symbol) => write!(f, "no instrument listed as {symbol}"),
+            OrderError::DuplicateSymbol(symbol) => {
+                write!(f, "an instrument is already listed as {symbol}")
+            }
+            OrderError::SymbolNotTrading { symbol, status } => {
+                write!(f, "{symbol} does not accept this request while {status:?}")
+            }
+            OrderError::MissingAccount(id) => write!(f, "order {id} has no account to fund it"),
+            OrderError::UnpricedBid(id) => {
+                write!(f, "bid {id} has no limit price to reserve funds against")
+            }
+            OrderError::InsufficientFunds { account, asset, required, available } => write!(
+                f,
+                "account {account} needs {required} {asset} but has {available} available"
+            ),
+            OrderError::TradeAlreadySettled(id) => {
+                write!(f, "trade {id} was already settled by the engine")
+            }
+            OrderError::CommitPhaseClosed => write!(f, "commitments are closed until the next round"),
+            OrderError::DuplicateCommitment => write!(f, "commitment was already submitted"),
+            OrderError::CommitmentMismatch(id) => {
+                write!(f, "order {id} and its salt match no open commitment")
+            }
+            OrderError::EarlyReveal(id) => {
+                write!(f, "order {id} was revealed before its reveal phase opened")
+            }
+            OrderError::LateReveal(id) => write!(f, "order {id} was revealed after its deadline"),
+        }
+    }
+}
+
+impl std::error::Error for OrderError {}
+
+/// Reasons a token ledger call reverts, after OpenZeppelin's ERC-20 errors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    InsufficientBalance { owner: String, balance: u128, needed: u128 },
+    InsufficientAllowance { owner: String, spender: String, allowance: u128, needed: u128 },
+    InvalidSender(String),
+    InvalidReceiver(String),
+    InvalidApprover(String),
+    InvalidSpender(String),
+    SupplyOverflow,
+    ArithmeticOverflow,
+    UnknownToken(String),
+    UnrepresentableAmount(Decimal),  // Negative, too precise for the decimals, or too large
+}
+
+impl fmt::Display for TokenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenError::InsufficientBalance { owner, balance, needed } => {
+                write!(f, "{owner} holds {balance} units but needs {needed}")
+            }
+            TokenError::InsufficientAllowance { owner, spender, allowance, needed } => write!(
+                f,
+                "{spender} may spend {allowance} units of {owner} but needs {needed}"
+            ),
+            TokenError::InvalidSender(address) => write!(f, "{address} cannot send tokens"),
+            TokenError::InvalidReceiver(address) => write!(f, "{address} cannot receive tokens"),
+            TokenError::InvalidApprover(address) => write!(f, "{address} cannot approve spenders"),
+            TokenError::InvalidSpender(address) => write!(f, "{address} cannot be approved"),
+            TokenError::SupplyOverflow => write!(f, "total supply would overflow"),
+            TokenError::ArithmeticOverflow => write!(f, "result does not fit in 128 bits"),
+            TokenError::UnknownToken(symbol) => write!(f, "no token ledger for {symbol}"),
+            TokenError::UnrepresentableAmount(amount) => {
+                write!(f, "{amount} cannot be expressed in token units")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TokenError {}
+
+/// Reasons a proposed settlement plan is rejected in a solver competition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolutionError {
+    UnknownIntent(String),
+    DuplicateFill(String),
+    MismatchedFill(String),  // Account or tokens differ from the intent's
+    InvalidFill { intent: String, sold: Decimal, bought: Decimal },
+    Overfill { intent: String, sold: Decimal, available: Decimal },
+    LimitPriceViolated { intent: String, sold: Decimal, bought: Decimal },
+    MissingPrice(String),
+    OffPrice { intent: String, bought: Decimal, expected: Decimal },  // Not at the plan's clearing prices
+    Unbalanced { token: String, sold: Decimal, bought: Decimal },
+    NoReferencePrice(String),
+}
+
+impl fmt::Display for SolutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolutionError::UnknownIntent(id) => write!(f, "no intent {id} in the batch"),
+            SolutionError::DuplicateFill(id) => write!(f, "intent {id} is filled more than once"),
+            SolutionError::MismatchedFill(id) => {
+                write!(f, "fill for intent {id} does not match its account or tokens")
+            }
+            SolutionError::InvalidFill { intent, sold, bought } => {
+                write!(f, "intent {intent} cannot sell {sold} for {bought}")
+            }
+            SolutionError::Overfill { intent, sold, available } => {
+                write!(f, "intent {intent} sells {sold} but offers only {available}")
+            }
+            SolutionError::LimitPriceViolated { intent, sold, bought } => {
+                write!(f, "intent {intent} receives {bought} for {sold}, below its limit")
+            }
+            SolutionError::MissingPrice(token) => write!(f, "no clearing price for {token}"),
+            SolutionError::OffPrice { intent, bought, expected } => {
+                write!(f, "intent {intent} receives {bought} where the clearing prices give {expected}")
+            }
+            SolutionError::Unbalanced { token, sold, bought } => {
+                write!(f, "{sold} {token} sold but {bought} bought")
+            }
+            SolutionError::NoReferencePrice(token) => {
+                write!(f, "no reference price to value surplus in {token}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolutionError {}
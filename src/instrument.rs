@@ -0,0 +1,181 @@
+use rust_decimal::Decimal;
+
+use crate::error::OrderError;
+use crate::order::Side;
+
+/// Trading rules of the instrument a book lists. Every rule is optional; an
+/// unset rule accepts anything positive.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InstrumentSpec {
+    tick_size: Option<Decimal>,        // Prices must be multiples of this
+    lot_size: Option<Decimal>,         // Quantities must be multiples of this
+    min_quantity: Option<Decimal>,
+    max_quantity: Option<Decimal>,
+    min_notional: Option<Decimal>,     // Smallest price * quantity for priced orders
+    price_precision: Option<u32>,      // Most decimal places a price may carry
+    base_asset: Option<String>,        // What the instrument trades, e.g. ETH in ETH-USD
+    quote_asset: Option<String>,       // What it is priced in, e.g. USD in ETH-USD
+}
+
+impl InstrumentSpec {
+    /// A spec with no trading rules.
+    pub fn new() -> Self {
+        InstrumentSpec::default()
+    }
+
+    pub fn with_tick_size(mut self, tick_size: Decimal) -> Self {
+        self.tick_size = Some(tick_size);
+        self
+    }
+
+    pub fn with_lot_size(mut self, lot_size: Decimal) -> Self {
+        self.lot_size = Some(lot_size);
+        self
+    }
+
+    pub fn with_min_quantity(mut self, min_quantity: Decimal) -> Self {
+        self.min_quantity = Some(min_quantity);
+        self
+    }
+
+    pub fn with_max_quantity(mut self, max_quantity: Decimal) -> Self {
+        self.max_quantity = Some(max_quantity);
+        self
+    }
+
+    pub fn with_min_notional(mut self, min_notional: Decimal) -> Self {
+        self.min_notional = Some(min_notional);
+        self
+    }
+
+    pub fn with_price_precision(mut self, decimal_places: u32) -> Self {
+        self.price_precision = Some(decimal_places);
+        self
+    }
+
+    /// Names the assets the instrument exchanges. Orders on an engine
+    /// listing with assets must then be funded from their account's balances.
+    pub fn with_assets(mut self, base_asset: impl Into<String>, quote_asset: impl Into<String>) -> Self {
+        self.base_asset = Some(base_asset.into());
+        self.quote_asset = Some(quote_asset.into());
+        self
+    }
+
+    pub fn tick_size(&self) -> Option<Decimal> {
+        self.tick_size
+    }
+
+    pub fn lot_size(&self) -> Option<Decimal> {
+        self.lot_size
+    }
+
+    pub fn min_quantity(&self) -> Option<Decimal> {
+        self.min_quantity
+    }
+
+    pub fn max_quantity(&self) -> Option<Decimal> {
+        self.max_quantity
+    }
+
+    pub fn min_notional(&self) -> Option<Decimal> {
+        self.min_notional
+    }
+
+    pub fn price_precision(&self) -> Option<u32> {
+        self.price_precision
+    }
+
+    pub fn base_asset(&self) -> Option<&str> {
+        self.base_asset.as_deref()
+    }
+
+    pub fn quote_asset(&self) -> Option<&str> {
+        self.quote_asset.as_deref()
+    }
+
+    /// Checks the rules themselves can be applied: tick and lot sizes must
+    /// be positive.
+    pub fn validate(&self) -> Result<(), OrderError> {
+        if let Some(tick_size) = self.tick_size.filter(|tick_size| *tick_size <= Decimal::ZERO) {
+            return Err(OrderError::InvalidTickSize(tick_size));
+        }
+        if let Some(lot_size) = self.lot_size.filter(|lot_size| *lot_size <= Decimal::ZERO) {
+            return Err(OrderError::InvalidLotSize(lot_size));
+        }
+        Ok(())
+    }
+
+    /// The asset an order on `side` pays with.
+    pub(crate) fn funding_asset(&self, side: Side) -> Option<&str> {
+        match side {
+            Side::Bid => self.quote_asset(),
+            Side::Ask => self.base_asset(),
+        }
+    }
+
+    pub fn validate_price(&self, price: Decimal) -> Result<(), OrderError> {
+        if price <= Decimal::ZERO {
+            return Err(OrderError::InvalidPrice(price));
+        }
+        if let Some(precision) = self.price_precision {
+            if price.normalize().scale() > precision {
+                return Err(OrderError::ExcessPricePrecision { price, precision });
+            }
+        }
+        if let Some(tick_size) = self.tick_size {
+            if !(price % tick_size).is_zero() {
+                return Err(OrderError::OffTickPrice { price, tick_size });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks an order's total quantity against the lot size and the
+    /// minimum and maximum order size.
+    pub fn validate_quantity(&self, quantity: Decimal) -> Result<(), OrderError> {
+        self.validate_lot(quantity)?;
+        if let Some(minimum) = self.min_quantity {
+            if quantity < minimum {
+                return Err(OrderError::QuantityBelowMinimum { quantity, minimum });
+            }
+        }
+        if let Some(maximum) = self.max_quantity {
+            if quantity > maximum {
+                return Err(OrderError::QuantityAboveMaximum { quantity, maximum });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks a quantity is positive and a whole number of lots.
+    pub fn validate_lot(&self, quantity: Decimal) -> Result<(), OrderError> {
+        if quantity <= Decimal::ZERO {
+            return Err(OrderError::InvalidQuantity(quantity));
+        }
+        if let Some(lot_size) = self.lot_size {
+            if !(quantity % lot_size).is_zero() {
+                return Err(OrderError::OffLotQuantity { quantity, lot_size });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks `price * quantity` is representable and meets the minimum
+    /// notional.
+    pub fn validate_notional(&self, price: Decimal, quantity: Decimal) -> Result<(), OrderError> {
+        let notional = notional(price, quantity)?;
+        if let Some(minimum) = self.min_notional {
+            if notional < minimum {
+                return Err(OrderError::NotionalBelowMinimum { notional, minimum });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `price * quantity`, or an error if it doesn't fit in a `Decimal`.
+pub(crate) fn notional(price: Decimal, quantity: Decimal) -> Result<Decimal, OrderError> {
+    price
+        .checked_mul(quantity)
+        .ok_or(OrderError::NotionalOverflow { price, quantity })
+}
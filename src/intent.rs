@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+
+/// A user's offer to sell up to `sell_amount` of one token for another, at a
+/// rate no worse than `buy_amount` per `sell_amount`. Partial fills are
+/// allowed at the same rate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intent {
+    pub(crate) id: String,
+    pub(crate) account: String,
+    pub(crate) sell_token: String,
+    pub(crate) sell_amount: Decimal,
+    pub(crate) buy_token: String,
+    pub(crate) buy_amount: Decimal,  // Least it accepts for the whole `sell_amount`
+}
+
+impl Intent {
+    /// Creates an intent. Nothing is validated here; solvers skip intents
+    /// with non-positive amounts, the same token on both sides, or a limit
+    /// price too large or small for a `Decimal`.
+    pub fn new(
+        id: impl Into<String>,
+        account: impl Into<String>,
+        sell_token: impl Into<String>,
+        sell_amount: Decimal,
+        buy_token: impl Into<String>,
+        buy_amount: Decimal,
+    ) -> Self {
+        Intent {
+            id: id.into(),
+            account: account.into(),
+            sell_token: sell_token.into(),
+            sell_amount,
+            buy_token: buy_token.into(),
+            buy_amount,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn sell_token(&self) -> &str {
+        &self.sell_token
+    }
+
+    pub fn sell_amount(&self) -> Decimal {
+        self.sell_amount
+    }
+
+    pub fn buy_token(&self) -> &str {
+        &self.buy_token
+    }
+
+    pub fn buy_amount(&self) -> Decimal {
+        self.buy_amount
+    }
+
+    /// Least it accepts per unit sold.
+    pub fn limit_price(&self) -> Decimal {
+        self.buy_amount / self.sell_amount
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        self.sell_amount > Decimal::ZERO
+            && self.buy_amount > Decimal::ZERO
+            && self.sell_token != self.buy_token
+            && self.buy_amount.checked_div(self.sell_amount).is_some_and(|limit| !limit.is_zero())
+    }
+
+    /// Whether receiving `bought` for `sold` respects the limit price.
+    /// Amounts too large to compare exactly count as not respecting it.
+    pub(crate) fn accepts(&self, sold: Decimal, bought: Decimal) -> bool {
+        match (bought.checked_mul(self.sell_amount), self.buy_amount.checked_mul(sold)) {
+            (Some(received), Some(required)) => received >= required,
+            _ => false,
+        }
+    }
+}
+
+/// How much of one intent a settlement plan executes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntentFill {
+    pub(crate) intent_id: String,
+    pub(crate) account: String,
+    pub(crate) sell_token: String,
+    pub(crate) buy_token: String,
+    pub(crate) sold: Decimal,
+    pub(crate) bought: Decimal,
+    pub(crate) surplus: Decimal,  // In the buy token
+}
+
+impl IntentFill {
+    /// Records `intent` selling `sold` and receiving `bought`.
+    pub fn new(intent: &Intent, sold: Decimal, bought: Decimal) -> Self {
+        IntentFill {
+            intent_id: intent.id.clone(),
+            account: intent.account.clone(),
+            sell_token: intent.sell_token.clone(),
+            buy_token: intent.buy_token.clone(),
+            sold,
+            bought,
+            surplus: bought - sold * intent.limit_price(),
+        }
+    }
+
+    pub fn intent_id(&self) -> &str {
+        &self.intent_id
+    }
+
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn sell_token(&self) -> &str {
+        &self.sell_token
+    }
+
+    pub fn buy_token(&self) -> &str {
+        &self.buy_token
+    }
+
+    pub fn sold(&self) -> Decimal {
+        self.sold
+    }
+
+    pub fn bought(&self) -> Decimal {
+        self.bought
+    }
+
+    /// What the user received beyond their limit price, in the buy token.
+    pub fn surplus(&self) -> Decimal {
+        self.surplus
+    }
+}
+
+/// Fills for a batch of intents and the uniform clearing price of every
+/// token they trade. An intent selling `sold` of token S receives
+/// `sold * price(S) / price(B)` of token B.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SettlementPlan {
+    pub(crate) prices: BTreeMap<String, Decimal>,
+    pub(crate) fills: Vec<IntentFill>,
+}
+
+impl SettlementPlan {
+    /// Assembles a plan, for example one proposed by an external solver.
+    pub fn new(prices: BTreeMap<String, Decimal>, fills: Vec<IntentFill>) -> Self {
+        SettlementPlan { prices, fills }
+    }
+
+    pub fn prices(&self) -> &BTreeMap<String, Decimal> {
+        &self.prices
+    }
+
+    pub fn price(&self, token: &str) -> Option<Decimal> {
+        self.prices.get(token).copied()
+    }
+
+    pub fn fills(&self) -> &[IntentFill] {
+        &self.fills
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fills.is_empty()
+    }
+
+    /// Surplus every account received, per buy token.
+    pub fn surplus_by_account(&self) -> BTreeMap<(String, String), Decimal> {
+        let mut surplus = BTreeMap::new();
+        for fill in &self.fills {
+            *surplus
+                .entry((fill.account.clone(), fill.buy_token.clone()))
+                .or_insert(Decimal::ZERO) += fill.surplus;
+        }
+        surplus
+    }
+}
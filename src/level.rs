@@ -0,0 +1,147 @@
+use std::collections::{HashMap, VecDeque};
+
+use rust_decimal::Decimal;
+
+use crate::order::{Order, Side};
+
+/// Where a queued order sits: its side, price level and queue sequence.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OrderLocation {
+    pub(crate) side: Side,
+    pub(crate) price: Decimal,
+    pub(crate) seq: u64,
+}
+
+/// FIFO queue of orders resting at one price.
+///
+/// Every order gets a queue sequence number when it joins the back of the
+/// level. Cancelled orders leave a hole instead of shifting the queue, so the
+/// sequence number stays a valid position for every other order and removal
+/// by id is O(1). Holes at the front are dropped as soon as they surface;
+/// once holes outnumber live orders the owner calls `compact` so churn
+/// behind a long-lived front order can't grow the queue without bound.
+///
+/// The level keeps running totals so depth queries never walk the queue;
+/// order quantities must therefore only change through `update`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PriceLevel {
+    orders: VecDeque<Option<Order>>,
+    head_seq: u64,              // Queue sequence of `orders[0]`
+    live: usize,
+    quantity: Decimal,          // Remaining quantity, hidden iceberg reserve included
+    visible_quantity: Decimal,  // What the level displays
+}
+
+impl PriceLevel {
+    /// Appends `order` to the back of the queue and returns its sequence.
+    pub(crate) fn push_back(&mut self, order: Order) -> u64 {
+        let seq = self.head_seq + self.orders.len() as u64;
+        self.add_totals(&order);
+        self.orders.push_back(Some(order));
+        self.live += 1;
+        seq
+    }
+
+    pub(crate) fn pop_front(&mut self) -> Option<Order> {
+        let order = self.orders.pop_front()??;
+        self.head_seq += 1;
+        self.live -= 1;
+        self.remove_totals(&order);
+        self.skip_holes();
+        Some(order)
+    }
+
+    /// Moves the order at `seq` to the back of the queue and returns its new
+    /// sequence.
+    pub(crate) fn requeue(&mut self, seq: u64) -> Option<u64> {
+        let order = self.take(seq)?;
+        Some(self.push_back(order))
+    }
+
+    pub(crate) fn get(&self, seq: u64) -> Option<&Order> {
+        let index = seq.checked_sub(self.head_seq)? as usize;
+        self.orders.get(index)?.as_ref()
+    }
+
+    /// Changes the order at `seq` in place, keeping the level totals in step,
+    /// and returns the updated order.
+    pub(crate) fn update(&mut self, seq: u64, change: impl FnOnce(&mut Order)) -> Option<&Order> {
+        let index = seq.checked_sub(self.head_seq)? as usize;
+        let order = self.orders.get_mut(index)?.as_mut()?;
+        self.quantity -= order.quantity;
+        self.visible_quantity -= order.visible_quantity();
+        change(order);
+        self.quantity += order.quantity;
+        self.visible_quantity += order.visible_quantity();
+        Some(order)
+    }
+
+    /// Removes the order at `seq`, leaving a hole in its place.
+    pub(crate) fn take(&mut self, seq: u64) -> Option<Order> {
+        let index = seq.checked_sub(self.head_seq)? as usize;
+        let order = self.orders.get_mut(index)?.take()?;
+        self.live -= 1;
+        self.remove_totals(&order);
+        self.skip_holes();
+        Some(order)
+    }
+
+    /// Whether holes outnumber live orders.
+    pub(crate) fn is_sparse(&self) -> bool {
+        self.orders.len() > 2 * self.live
+    }
+
+    /// Closes every hole, renumbering the orders from the front of the
+    /// queue and updating their entries in `index`. Sequences taken before
+    /// the call are invalid after it.
+    pub(crate) fn compact(&mut self, index: &mut HashMap<String, OrderLocation>) {
+        self.orders.retain(Option::is_some);
+        for (seq, order) in self.entries() {
+            if let Some(location) = index.get_mut(&order.id) {
+                location.seq = seq;
+            }
+        }
+    }
+
+    /// Live orders in queue order, with their sequences.
+    pub(crate) fn entries(&self) -> impl Iterator<Item = (u64, &Order)> {
+        let head_seq = self.head_seq;
+        self.orders
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, order)| Some((head_seq + index as u64, order.as_ref()?)))
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    pub(crate) fn order_count(&self) -> usize {
+        self.live
+    }
+
+    pub(crate) fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+
+    pub(crate) fn visible_quantity(&self) -> Decimal {
+        self.visible_quantity
+    }
+
+    fn add_totals(&mut self, order: &Order) {
+        self.quantity += order.quantity;
+        self.visible_quantity += order.visible_quantity();
+    }
+
+    fn remove_totals(&mut self, order: &Order) {
+        self.quantity -= order.quantity;
+        self.visible_quantity -= order.visible_quantity();
+    }
+
+    fn skip_holes(&mut self) {
+        while let Some(None) = self.orders.front() {
+            self.orders.pop_front();
+            self.head_seq += 1;
+        }
+    }
+}
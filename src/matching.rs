@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::level::{OrderLocation, PriceLevel};
+use crate::order::Order;
+use crate::report::ExecutionReport;
+use crate::self_trade::{self, SelfTradePrevention};
+use crate::trade::{Execution, Trade};
+
+/// Decides how an incoming quantity is shared among the orders resting at
+/// one price level.
+pub trait MatchingPolicy: Send + Sync {
+    /// Splits `quantity` among `orders`, the level's resting orders in queue
+    /// order. Returns `(index into orders, quantity)` pairs in the order the
+    /// fills should be applied.
+    ///
+    /// No pair may exceed its order's visible quantity, and the total should
+    /// reach `quantity` whenever the level shows that much.
+    fn allocate(&self, orders: &[&Order], quantity: Decimal) -> Vec<(usize, Decimal)>;
+
+    /// Whether `allocate` only ever fills a prefix of the queue. The book
+    /// then hands over just enough orders to cover the quantity instead of
+    /// the whole level.
+    fn fills_in_queue_order(&self) -> bool {
+        false
+    }
+
+    /// The lot size fills are rounded to, if the policy rounds at all. A
+    /// book refuses a policy whose lots aren't whole lots of its own.
+    fn lot_size(&self) -> Option<Decimal> {
+        None
+    }
+}
+
+/// Price-time priority: the front of the queue fills first.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fifo;
+
+impl MatchingPolicy for Fifo {
+    fn allocate(&self, orders: &[&Order], quantity: Decimal) -> Vec<(usize, Decimal)> {
+        let mut remaining: Vec<Decimal> = orders.iter().map(|order| order.visible_quantity()).collect();
+        let mut fills = vec![Decimal::ZERO; orders.len()];
+        allocate_fifo(&mut fills, &mut remaining, quantity);
+        into_allocations(fills)
+    }
+
+    fn fills_in_queue_order(&self) -> bool {
+        true
+    }
+}
+
+/// Pro-rata allocation: each order gets a share proportional to its visible
+/// quantity, rounded down to whole lots, with rounding leftovers handed out
+/// in queue order.
+///
+/// Optionally the order at the front of the queue fills first (top-of-book
+/// priority), and a set of lead market maker accounts receives a fixed share
+/// of the incoming quantity before the pro-rata pass.
+#[derive(Debug, Clone, Default)]
+pub struct ProRata {
+    lot_size: Decimal,
+    top_of_book_priority: bool,
+    lead_market_makers: Vec<String>,
+    lead_market_maker_share: Decimal,
+}
+
+impl ProRata {
+    /// Pro-rata allocation in multiples of `lot_size`. A zero lot size splits
+    /// quantities exactly, so it only suits books without a lot size.
+    pub fn new(lot_size: Decimal) -> Self {
+        ProRata {
+            lot_size,
+            ..ProRata::default()
+        }
+    }
+
+    /// Fills the order at the front of the queue before sharing the rest.
+    pub fn with_top_of_book_priority(mut self) -> Self {
+        self.top_of_book_priority = true;
+        self
+    }
+
+    /// Reserves `share` (between 0 and 1) of the incoming quantity for orders
+    /// from `accounts`, split pro-rata among them.
+    pub fn with_lead_market_makers(
+        mut self,
+        accounts: impl IntoIterator<Item = impl Into<String>>,
+        share: Decimal,
+    ) -> Self {
+        self.lead_market_makers = accounts.into_iter().map(Into::into).collect();
+        self.lead_market_maker_share = share;
+        self
+    }
+
+    fn is_lead_market_maker(&self, order: &Order) -> bool {
+        order
+            .account()
+            .is_some_and(|account| self.lead_market_makers.iter().any(|lmm| lmm == account))
+    }
+
+    fn round_down(&self, quantity: Decimal) -> Decimal {
+        if self.lot_size.is_zero() {
+            quantity
+        } else {
+            (quantity / self.lot_size).floor() * self.lot_size
+        }
+    }
+
+    /// Shares `amount` among the orders picked by `eligible`, proportionally
+    /// to what they have left, and returns how much was handed out.
+    fn allocate_pro_rata(
+        &self,
+        fills: &mut [Decimal],
+        remaining: &mut [Decimal],
+        eligible: &[bool],
+        amount: Decimal,
+    ) -> Decimal {
+        let total: Decimal = remaining
+            .iter()
+            .zip(eligible)
+            .filter(|(_, eligible)| **eligible)
+            .map(|(remaining, _)| *remaining)
+            .sum();
+        if total.is_zero() || amount.is_zero() {
+            return Decimal::ZERO;
+        }
+
+        let mut allocated = Decimal::ZERO;
+        for index in 0..remaining.len() {
+            if !eligible[index] {
+                continue;
+            }
+            let share = self
+                .round_down(amount * remaining[index] / total)
+                .min(remaining[index])
+                .min(amount - allocated);
+            fills[index] += share;
+            remaining[index] -= share;
+            allocated += share;
+        }
+        allocated
+    }
+}
+
+impl MatchingPolicy for ProRata {
+    fn allocate(&self, orders: &[&Order], quantity: Decimal) -> Vec<(usize, Decimal)> {
+        let mut remaining: Vec<Decimal> = orders.iter().map(|order| order.visible_quantity()).collect();
+        let mut fills = vec![Decimal::ZERO; orders.len()];
+        let mut left = quantity;
+
+        if self.top_of_book_priority {
+            if let Some(front) = remaining.first_mut() {
+                let fill = left.min(*front);
+                fills[0] += fill;
+                *front -= fill;
+                left -= fill;
+            }
+        }
+
+        if !self.lead_market_makers.is_empty() {
+            let eligible: Vec<bool> = orders.iter().map(|order| self.is_lead_market_maker(order)).collect();
+            let reserved = self.round_down(left * self.lead_market_maker_share);
+            left -= self.allocate_pro_rata(&mut fills, &mut remaining, &eligible, reserved);
+        }
+
+        let everyone = vec![true; orders.len()];
+        left -= self.allocate_pro_rata(&mut fills, &mut remaining, &everyone, left);
+
+        allocate_fifo(&mut fills, &mut remaining, left);
+        into_allocations(fills)
+    }
+
+    fn lot_size(&self) -> Option<Decimal> {
+        Some(self.lot_size)
+    }
+}
+
+/// Shares `amount` among `quantities` pro-rata in multiples of `lot_size`,
+/// handing rounding leftovers out in queue order.
+pub(crate) fn ration(lot_size: Decimal, quantities: &[Decimal], amount: Decimal) -> Vec<Decimal> {
+    let mut remaining = quantities.to_vec();
+    let mut fills = vec![Decimal::ZERO; quantities.len()];
+    let everyone = vec![true; quantities.len()];
+    let left = amount - ProRata::new(lot_size).allocate_pro_rata(&mut fills, &mut remaining, &everyone, amount);
+    allocate_fifo(&mut fills, &mut remaining, left);
+    fills
+}
+
+/// Hands out `amount` in queue order.
+fn allocate_fifo(fills: &mut [Decimal], remaining: &mut [Decimal], mut amount: Decimal) {
+    for (fill, remaining) in fills.iter_mut().zip(remaining.iter_mut()) {
+        if amount.is_zero() {
+            break;
+        }
+        let share = amount.min(*remaining);
+        *fill += share;
+        *remaining -= share;
+        amount -= share;
+    }
+}
+
+fn into_allocations(fills: Vec<Decimal>) -> Vec<(usize, Decimal)> {
+    fills
+        .into_iter()
+        .enumerate()
+        .filter(|(_, fill)| *fill > Decimal::ZERO)
+        .collect()
+}
+
+/// Trades `taker` against one price level as `policy` allocates it. Returns
+/// whether anything changed, so the caller can stop on a level that can't
+/// make progress.
+pub(crate) fn match_level(
+    policy: &dyn MatchingPolicy,
+    self_trade_prevention: SelfTradePrevention,
+    taker: &mut Order,
+    price: Decimal,
+    level: &mut PriceLevel,
+    index: &mut HashMap<String, OrderLocation>,
+    execution: &mut Execution,
+) -> bool {
+    let allocations: Vec<(u64, Decimal)> = {
+        let mut covered = Decimal::ZERO;
+        let entries: Vec<(u64, &Order)> = level
+            .entries()
+            .take_while(|(_, order)| {
+                let needed = covered < taker.quantity || !policy.fills_in_queue_order();
+                covered += order.visible_quantity();
+                needed
+            })
+            .collect();
+        let orders: Vec<&Order> = entries.iter().map(|(_, order)| *order).collect();
+        policy
+            .allocate(&orders, taker.quantity)
+            .into_iter()
+            .filter_map(|(position, quantity)| {
+                let (seq, order) = entries.get(position)?;
+                let quantity = quantity.min(order.visible_quantity());
+                (quantity > Decimal::ZERO).then_some((*seq, quantity))
+            })
+            .collect()
+    };
+
+    let mut progressed = false;
+    for (seq, quantity) in allocations {
+        if taker.quantity.is_zero() {
+            break;
+        }
+        // Meeting our own order changes the level, so let the policy reallocate
+        if self_trade::prevent(self_trade_prevention, taker, level, seq, index, execution) {
+            return true;
+        }
+        let trade_quantity = quantity.min(taker.quantity);
+        let Some(maker) = level.update(seq, |maker| maker.fill(trade_quantity)) else {
+            continue;
+        };
+        taker.fill(trade_quantity);
+        // Id, timestamp and sequence are stamped by the book
+        execution.trades.push(Trade {
+            id: 0,
+            maker_order_id: maker.id.clone(),
+            taker_order_id: taker.id.clone(),
+            maker_account: maker.account.clone(),
+            taker_account: taker.account.clone(),
+            aggressor: taker.side,
+            price,
+            quantity: trade_quantity,
+            maker_remaining: maker.quantity,
+            taker_remaining: taker.quantity,
+            timestamp: 0,
+            sequence: 0,
+            settled: false,
+        });
+        execution.reports.push(ExecutionReport::fill(maker, price, trade_quantity));
+        execution.reports.push(ExecutionReport::fill(taker, price, trade_quantity));
+        progressed = true;
+
+        // Remove filled maker order, or send a consumed iceberg slice to the back
+        if maker.quantity.is_zero() {
+            if let Some(filled) = level.take(seq) {
+                index.remove(&filled.id);
+            }
+        } else if maker.visible_quantity().is_zero() {
+            let id = maker.id.clone();
+            level.update(seq, Order::refresh_display);
+            if let (Some(seq), Some(location)) = (level.requeue(seq), index.get_mut(&id)) {
+                location.seq = seq;
+            }
+        }
+    }
+    progressed
+}
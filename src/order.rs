@@ -0,0 +1,279 @@
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Bid,  // Buy order
+    Ask,  // Sell order
+}
+
+impl Side {
+    /// The side this order would trade against.
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OrderType {
+    Limit,      // Trades up to its price, rests the remainder
+    Market,     // Trades at any price, never rests
+    Stop,       // Held until the stop price trades, then enters as a market order
+    StopLimit,  // Held until the stop price trades, then enters as a limit order
+}
+
+/// How long an order may stay on the book.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TimeInForce {
+    #[default]
+    Gtc,            // Good-till-cancelled
+    Ioc,            // Immediate-or-cancel: trade what you can, cancel the rest
+    Fok,            // Fill-or-kill: trade in full or not at all
+    PostOnly,       // Rejected if it would take liquidity
+    PostOnlySlide,  // Repriced one tick behind the opposite touch if it would take liquidity
+    Gtt(u64),       // Good-till-time: expires once the book clock reaches this timestamp
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Order {
+    pub(crate) id: String,
+    pub(crate) order_type: OrderType,
+    pub(crate) price: Option<Decimal>,       // Limit price, `None` for market orders
+    pub(crate) stop_price: Option<Decimal>,  // Trigger price for stop orders
+    pub(crate) quantity: Decimal,
+    pub(crate) filled_quantity: Decimal,
+    pub(crate) display_quantity: Option<Decimal>,  // Iceberg slice size, `None` shows everything
+    pub(crate) visible_quantity: Decimal,          // What is left of the current iceberg slice
+    pub(crate) side: Side,
+    pub(crate) account: Option<String>,  // Owner, for self-trade prevention
+    pub(crate) timestamp: u64,
+    pub(crate) time_in_force: TimeInForce,
+}
+
+impl Order {
+    /// Creates a limit order. Nothing is validated here; `OrderBook::add_order`
+    /// rejects orders that don't fit the book.
+    pub fn new(
+        id: impl Into<String>,
+        side: Side,
+        price: Decimal,
+        quantity: Decimal,
+        timestamp: u64,
+    ) -> Self {
+        Order {
+            id: id.into(),
+            order_type: OrderType::Limit,
+            price: Some(price),
+            stop_price: None,
+            quantity,
+            filled_quantity: Decimal::ZERO,
+            display_quantity: None,
+            visible_quantity: quantity,
+            side,
+            account: None,
+            timestamp,
+            time_in_force: TimeInForce::Gtc,
+        }
+    }
+
+    /// Creates a market order: it sweeps the opposite side until filled or
+    /// the book runs dry, and whatever is left is cancelled.
+    pub fn market(id: impl Into<String>, side: Side, quantity: Decimal, timestamp: u64) -> Self {
+        Order {
+            id: id.into(),
+            order_type: OrderType::Market,
+            price: None,
+            stop_price: None,
+            quantity,
+            filled_quantity: Decimal::ZERO,
+            display_quantity: None,
+            visible_quantity: quantity,
+            side,
+            account: None,
+            timestamp,
+            time_in_force: TimeInForce::Gtc,
+        }
+    }
+
+    /// Creates a stop order. It stays out of the visible book until a trade
+    /// prints at or through `stop_price` (at or above for bids, at or below
+    /// for asks), then enters as a market order.
+    pub fn stop(
+        id: impl Into<String>,
+        side: Side,
+        stop_price: Decimal,
+        quantity: Decimal,
+        timestamp: u64,
+    ) -> Self {
+        Order {
+            order_type: OrderType::Stop,
+            stop_price: Some(stop_price),
+            ..Order::market(id, side, quantity, timestamp)
+        }
+    }
+
+    /// Creates a stop-limit order: like a stop order, but it enters the book
+    /// as a limit order at `price` once triggered.
+    pub fn stop_limit(
+        id: impl Into<String>,
+        side: Side,
+        stop_price: Decimal,
+        price: Decimal,
+        quantity: Decimal,
+        timestamp: u64,
+    ) -> Self {
+        Order {
+            order_type: OrderType::StopLimit,
+            stop_price: Some(stop_price),
+            ..Order::new(id, side, price, quantity, timestamp)
+        }
+    }
+
+    pub fn with_time_in_force(mut self, time_in_force: TimeInForce) -> Self {
+        self.time_in_force = time_in_force;
+        self
+    }
+
+    /// Turns the order into an iceberg: only `display_quantity` is shown at a
+    /// time, and each consumed slice is refreshed from the hidden reserve at
+    /// the back of its price level's queue.
+    pub fn with_display_quantity(mut self, display_quantity: Decimal) -> Self {
+        self.display_quantity = Some(display_quantity);
+        self
+    }
+
+    /// Tags the order with the account that owns it.
+    pub fn with_account(mut self, account: impl Into<String>) -> Self {
+        self.account = Some(account.into());
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn order_type(&self) -> OrderType {
+        self.order_type
+    }
+
+    /// Limit price, `None` for market orders.
+    pub fn price(&self) -> Option<Decimal> {
+        self.price
+    }
+
+    /// Trigger price, `None` unless this is a stop or stop-limit order.
+    pub fn stop_price(&self) -> Option<Decimal> {
+        self.stop_price
+    }
+
+    /// Remaining (unfilled) quantity.
+    pub fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+
+    /// Quantity filled so far.
+    pub fn filled_quantity(&self) -> Decimal {
+        self.filled_quantity
+    }
+
+    /// Iceberg slice size, `None` for fully displayed orders.
+    pub fn display_quantity(&self) -> Option<Decimal> {
+        self.display_quantity
+    }
+
+    /// Quantity shown on the book: the current slice for icebergs, the whole
+    /// remaining quantity otherwise.
+    pub fn visible_quantity(&self) -> Decimal {
+        match self.display_quantity {
+            Some(_) => self.visible_quantity,
+            None => self.quantity,
+        }
+    }
+
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    pub fn account(&self) -> Option<&str> {
+        self.account.as_deref()
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn time_in_force(&self) -> TimeInForce {
+        self.time_in_force
+    }
+
+    /// Whether an unfilled remainder may rest on the book.
+    pub(crate) fn can_rest(&self) -> bool {
+        self.order_type == OrderType::Limit
+            && !matches!(self.time_in_force, TimeInForce::Ioc | TimeInForce::Fok)
+    }
+
+    /// Records a fill of `quantity`, taken out of the visible slice first.
+    pub(crate) fn fill(&mut self, quantity: Decimal) {
+        self.quantity -= quantity;
+        self.filled_quantity += quantity;
+        self.visible_quantity = (self.visible_quantity - quantity).max(Decimal::ZERO);
+    }
+
+    /// Cuts the remaining quantity down to `quantity` without losing priority.
+    pub(crate) fn reduce_to(&mut self, quantity: Decimal) {
+        self.quantity = quantity;
+        self.visible_quantity = self.visible_quantity.min(quantity);
+    }
+
+    /// Shows a fresh iceberg slice from the hidden reserve.
+    pub(crate) fn refresh_display(&mut self) {
+        self.visible_quantity = match self.display_quantity {
+            Some(display_quantity) => display_quantity.min(self.quantity),
+            None => self.quantity,
+        };
+    }
+
+    /// Whether both orders belong to the same known account.
+    pub(crate) fn same_account(&self, other: &Order) -> bool {
+        self.account.is_some() && self.account == other.account
+    }
+
+    pub(crate) fn is_stop(&self) -> bool {
+        matches!(self.order_type, OrderType::Stop | OrderType::StopLimit)
+    }
+
+    /// Whether a trade at `last_price` sets off this stop order.
+    pub(crate) fn is_triggered_by(&self, last_price: Decimal) -> bool {
+        match (self.stop_price, self.side) {
+            (None, _) => false,
+            (Some(stop_price), Side::Bid) => last_price >= stop_price,
+            (Some(stop_price), Side::Ask) => last_price <= stop_price,
+        }
+    }
+
+    /// Turns a triggered stop order into the order it releases.
+    pub(crate) fn into_triggered(mut self) -> Self {
+        self.order_type = match self.order_type {
+            OrderType::Stop => OrderType::Market,
+            OrderType::StopLimit => OrderType::Limit,
+            other => other,
+        };
+        self
+    }
+
+    /// Whether this order is willing to trade against a resting order at `price`.
+    pub(crate) fn accepts(&self, price: Decimal) -> bool {
+        match (self.price, self.side) {
+            (None, _) => true,
+            (Some(limit), Side::Bid) => price <= limit,
+            (Some(limit), Side::Ask) => price >= limit,
+        }
+    }
+
+    /// Whether a resting order has expired by book time `now`.
+    pub(crate) fn is_expired(&self, now: u64) -> bool {
+        matches!(self.time_in_force, TimeInForce::Gtt(expires_at) if expires_at <= now)
+    }
+}
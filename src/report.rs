@@ -0,0 +1,118 @@
+use rust_decimal::Decimal;
+
+use crate::error::OrderError;
+use crate::order::{Order, Side};
+
+/// What happened to an order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReportKind {
+    Accepted,
+    PartiallyFilled { price: Decimal, quantity: Decimal },
+    Filled { price: Decimal, quantity: Decimal },
+    Replaced,  // Price or leaves quantity changed by an amend or self-trade prevention
+    Cancelled,
+    Expired,
+    Rejected(OrderError),  // A rejected amend leaves the order working as it was
+}
+
+impl ReportKind {
+    /// Whether the order is finished after this report. A rejected amend is
+    /// the exception: its report keeps the order's leaves quantity.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ReportKind::Filled { .. }
+                | ReportKind::Cancelled
+                | ReportKind::Expired
+                | ReportKind::Rejected(_)
+        )
+    }
+}
+
+/// One step in an order's lifecycle, with the order's running totals after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecutionReport {
+    pub(crate) order_id: String,
+    pub(crate) side: Option<Side>,  // Unknown for an amend of an order the book doesn't hold
+    pub(crate) price: Option<Decimal>,
+    pub(crate) kind: ReportKind,
+    pub(crate) cumulative_quantity: Decimal,
+    pub(crate) leaves_quantity: Decimal,
+}
+
+impl ExecutionReport {
+    pub(crate) fn new(order: &Order, kind: ReportKind) -> Self {
+        let leaves_quantity = if kind.is_terminal() {
+            Decimal::ZERO
+        } else {
+            order.quantity
+        };
+        ExecutionReport {
+            order_id: order.id.clone(),
+            side: Some(order.side),
+            price: order.price,
+            kind,
+            cumulative_quantity: order.filled_quantity,
+            leaves_quantity,
+        }
+    }
+
+    /// Reports an amend of order `id` refused with `error`. The order, if
+    /// the book holds it, carries on unchanged.
+    pub(crate) fn amend_rejected(id: &str, order: Option<&Order>, error: OrderError) -> Self {
+        match order {
+            Some(order) => ExecutionReport {
+                leaves_quantity: order.quantity,
+                ..ExecutionReport::new(order, ReportKind::Rejected(error))
+            },
+            None => ExecutionReport {
+                order_id: id.to_string(),
+                side: None,
+                price: None,
+                kind: ReportKind::Rejected(error),
+                cumulative_quantity: Decimal::ZERO,
+                leaves_quantity: Decimal::ZERO,
+            },
+        }
+    }
+
+    /// Reports a fill of `quantity` at `price` on an order already updated
+    /// for it.
+    pub(crate) fn fill(order: &Order, price: Decimal, quantity: Decimal) -> Self {
+        let kind = if order.quantity.is_zero() {
+            ReportKind::Filled { price, quantity }
+        } else {
+            ReportKind::PartiallyFilled { price, quantity }
+        };
+        ExecutionReport::new(order, kind)
+    }
+
+    pub fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    /// The order's side, `None` if the report rejects an amend of an order
+    /// the book doesn't hold.
+    pub fn side(&self) -> Option<Side> {
+        self.side
+    }
+
+    /// The order's limit price, `None` for market and stop orders.
+    pub fn price(&self) -> Option<Decimal> {
+        self.price
+    }
+
+    pub fn kind(&self) -> &ReportKind {
+        &self.kind
+    }
+
+    /// Total quantity filled so far.
+    pub fn cumulative_quantity(&self) -> Decimal {
+        self.cumulative_quantity
+    }
+
+    /// Quantity still working; zero once the order is finished.
+    pub fn leaves_quantity(&self) -> Decimal {
+        self.leaves_quantity
+    }
+}
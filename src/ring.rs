@@ -0,0 +1,250 @@
+use std::collections::{BTreeMap, HashMap};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+use crate::intent::{Intent, IntentFill, SettlementPlan};
+
+/// Finds coincidences of wants among intents: rings of users such as
+/// A→B→C→A where each one sells what the next wants, so they trade among
+/// themselves with no book or outside liquidity involved.
+///
+/// The search is greedy. Rings are tried shortest first, then by token
+/// name, each using the most generous intent on every hop, and the first
+/// that clears is executed at the largest volume all its intents allow.
+/// This repeats until no ring clears. Each token gets one price for the
+/// whole batch: a ring through tokens already priced by an earlier ring
+/// keeps their ratio and shares out only the surplus on its other hops.
+/// Within a ring, surplus is split so every hop beats its limit by the same
+/// factor.
+#[derive(Debug, Clone, Copy)]
+pub struct RingSolver {
+    max_ring_length: usize,
+}
+
+impl Default for RingSolver {
+    fn default() -> Self {
+        RingSolver { max_ring_length: 4 }
+    }
+}
+
+/// A ring that clears: its intents in order, what each sells and the
+/// prices it sets.
+struct Ring {
+    intents: Vec<usize>,
+    sold: Vec<Decimal>,
+    prices: Vec<(String, Decimal)>,
+}
+
+impl RingSolver {
+    pub fn new() -> Self {
+        RingSolver::default()
+    }
+
+    /// Longest ring, in intents, the search considers. Two is a plain swap
+    /// between two users.
+    pub fn with_max_ring_length(mut self, max_ring_length: usize) -> Self {
+        self.max_ring_length = max_ring_length.max(2);
+        self
+    }
+
+    pub fn max_ring_length(&self) -> usize {
+        self.max_ring_length
+    }
+
+    pub fn solve(&self, intents: &[Intent]) -> SettlementPlan {
+        let mut remaining: Vec<Decimal> = intents
+            .iter()
+            .map(|intent| if intent.is_valid() { intent.sell_amount } else { Decimal::ZERO })
+            .collect();
+        let mut prices = BTreeMap::new();
+        let mut executed = vec![(Decimal::ZERO, Decimal::ZERO); intents.len()];
+
+        while let Some(ring) = self.find_ring(intents, &remaining, &prices) {
+            let count = ring.intents.len();
+            for position in 0..count {
+                let index = ring.intents[position];
+                let sold = ring.sold[position];
+                let bought = ring.sold[(position + 1) % count];
+                remaining[index] -= sold;
+                executed[index].0 += sold;
+                executed[index].1 += bought;
+            }
+            prices.extend(ring.prices);
+        }
+
+        let fills = intents
+            .iter()
+            .zip(executed)
+            .filter(|(_, (sold, _))| *sold > Decimal::ZERO)
+            .map(|(intent, (sold, bought))| IntentFill::new(intent, sold, bought))
+            .collect();
+        SettlementPlan { prices, fills }
+    }
+
+    /// The first ring, shortest first, that clears against what is left.
+    fn find_ring(
+        &self,
+        intents: &[Intent],
+        remaining: &[Decimal],
+        prices: &BTreeMap<String, Decimal>,
+    ) -> Option<Ring> {
+        // Most generous intent with something left on every hop
+        let mut hops: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+        for (index, intent) in intents.iter().enumerate() {
+            if remaining[index] <= Decimal::ZERO {
+                continue;
+            }
+            let best = hops
+                .entry(intent.sell_token.as_str())
+                .or_default()
+                .entry(intent.buy_token.as_str())
+                .or_insert(index);
+            let key = |index: usize| (intents[index].limit_price(), &intents[index].id);
+            if key(index) < key(*best) {
+                *best = index;
+            }
+        }
+
+        for length in 2..=self.max_ring_length {
+            for start in hops.keys() {
+                let search = Search {
+                    intents,
+                    remaining,
+                    prices,
+                    hops: &hops,
+                    start,
+                    length,
+                };
+                if let Some(ring) = search.extend(start, &mut Vec::new()) {
+                    return Some(ring);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// A depth-first search for rings of exactly `length` hops through `start`.
+struct Search<'a> {
+    intents: &'a [Intent],
+    remaining: &'a [Decimal],
+    prices: &'a BTreeMap<String, Decimal>,
+    hops: &'a BTreeMap<&'a str, BTreeMap<&'a str, usize>>,
+    start: &'a str,
+    length: usize,
+}
+
+impl Search<'_> {
+    /// Extends `path`, intents leading from `start` to `token`, into rings
+    /// back to `start`. Tokens after `start` must sort above it, so every
+    /// ring is found once, from its lowest token.
+    fn extend(&self, token: &str, path: &mut Vec<usize>) -> Option<Ring> {
+        for (next, index) in self.hops.get(token)? {
+            let closes = *next == self.start;
+            if closes != (path.len() + 1 == self.length) {
+                continue;
+            }
+            let revisits = path.iter().any(|i| self.intents[*i].sell_token == *next);
+            if !closes && (*next < self.start || revisits) {
+                continue;
+            }
+            path.push(*index);
+            let found = if closes {
+                clear(self.intents, self.remaining, self.prices, path)
+            } else {
+                self.extend(next, path)
+            };
+            path.pop();
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+}
+
+/// Prices and sizes the ring `path`, or `None` if some intent's limit
+/// can't be met at prices consistent with those already set.
+fn clear(
+    intents: &[Intent],
+    remaining: &[Decimal],
+    prices: &BTreeMap<String, Decimal>,
+    path: &[usize],
+) -> Option<Ring> {
+    let count = path.len();
+    let token = |position: usize| intents[path[position % count]].sell_token.as_str();
+    let mut local: HashMap<&str, Decimal> = (0..count)
+        .filter_map(|position| Some((token(position), *prices.get(token(position))?)))
+        .collect();
+
+    // Anchors are tokens whose price is fixed; the hops between two
+    // anchors share out the slack their price ratio leaves over the limits
+    let mut anchors: Vec<usize> = (0..count).filter(|position| local.contains_key(token(*position))).collect();
+    if anchors.is_empty() {
+        local.insert(token(0), Decimal::ONE);
+        anchors.push(0);
+    }
+    for (at, &from) in anchors.iter().enumerate() {
+        let to = anchors.get(at + 1).copied().unwrap_or(anchors[0] + count);
+        let ratio = local[token(from)].checked_div(local[token(to)])?;
+        let limits: Vec<Decimal> = (from..to).map(|position| intents[path[position % count]].limit_price()).collect();
+        let factor = slack_factor(ratio, &limits)?;
+        for (offset, limit) in limits.iter().enumerate().take(limits.len() - 1) {
+            let price = local[token(from + offset)].checked_div(limit.checked_mul(factor)?)?;
+            if price.is_zero() {
+                return None;
+            }
+            local.insert(token(from + offset + 1), price);
+        }
+    }
+
+    // Size the ring by the intent that can sell the least value
+    let values: Vec<Decimal> = (0..count)
+        .map(|position| remaining[path[position]].checked_mul(local[token(position)]))
+        .collect::<Option<_>>()?;
+    let binding = (0..count).min_by_key(|position| values[*position])?;
+    let volume = values[binding];
+    let sold: Vec<Decimal> = (0..count)
+        .map(|position| {
+            if position == binding {
+                Some(remaining[path[position]])
+            } else {
+                Some(volume.checked_div(local[token(position)])?.min(remaining[path[position]]))
+            }
+        })
+        .collect::<Option<_>>()?;
+    for position in 0..count {
+        let intent = &intents[path[position]];
+        let bought = sold[(position + 1) % count];
+        if sold[position] <= Decimal::ZERO || !intent.accepts(sold[position], bought) {
+            return None;
+        }
+    }
+
+    Some(Ring {
+        intents: path.to_vec(),
+        sold,
+        prices: local.into_iter().map(|(token, price)| (token.to_string(), price)).collect(),
+    })
+}
+
+/// The factor by which each of `limits` is beaten when the hops share
+/// out the slack `ratio` leaves over their product evenly, or `None` if the
+/// limits need more than `ratio`.
+///
+/// Worked out in log space, as the product of a few limits easily over- or
+/// underflows a `Decimal`. The factor is shaded down a hair so rounding
+/// can't leave the ring short; the hop closing the segment keeps the rest.
+fn slack_factor(ratio: Decimal, limits: &[Decimal]) -> Option<Decimal> {
+    let ln = |value: Decimal| value.to_f64().filter(|value| *value > 0.0).map(f64::ln);
+    let mut slack = ln(ratio)?;
+    for limit in limits {
+        slack -= ln(*limit)?;
+    }
+    // Anything this close to even is left for the exact check on amounts
+    if slack < -1e-12 {
+        return None;
+    }
+    let factor = (slack.max(0.0) / limits.len() as f64).exp() * (1.0 - 1e-12);
+    Some(Decimal::from_f64(factor)?.max(Decimal::ONE))
+}
@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::level::{OrderLocation, PriceLevel};
+use crate::order::Order;
+use crate::report::{ExecutionReport, ReportKind};
+use crate::trade::Execution;
+
+/// What the book does when an incoming order meets a resting order from the
+/// same account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SelfTradePrevention {
+    Allow,               // Let the orders trade
+    #[default]
+    CancelNewest,        // Cancel the incoming order's remainder
+    CancelOldest,        // Cancel the resting order and keep matching
+    CancelBoth,          // Cancel both
+    DecrementAndCancel,  // Take the smaller quantity off both, cancelling whichever hits zero
+}
+
+/// Applies `mode` if `taker` is about to trade against its own resting order
+/// at `seq` in `level`. Returns whether it did, in which case the two must not
+/// trade.
+pub(crate) fn prevent(
+    mode: SelfTradePrevention,
+    taker: &mut Order,
+    level: &mut PriceLevel,
+    seq: u64,
+    index: &mut HashMap<String, OrderLocation>,
+    execution: &mut Execution,
+) -> bool {
+    let Some(maker) = level.get(seq) else {
+        return false;
+    };
+    if mode == SelfTradePrevention::Allow || !taker.same_account(maker) {
+        return false;
+    }
+
+    let (taker_cut, maker_cut) = match mode {
+        SelfTradePrevention::Allow | SelfTradePrevention::CancelNewest => (taker.quantity, Decimal::ZERO),
+        SelfTradePrevention::CancelOldest => (Decimal::ZERO, maker.quantity),
+        SelfTradePrevention::CancelBoth => (taker.quantity, maker.quantity),
+        SelfTradePrevention::DecrementAndCancel => {
+            let quantity = taker.quantity.min(maker.quantity);
+            (quantity, quantity)
+        }
+    };
+
+    if maker_cut == maker.quantity {
+        if let Some(cancelled) = level.take(seq) {
+            index.remove(&cancelled.id);
+            execution.reports.push(ExecutionReport::new(&cancelled, ReportKind::Cancelled));
+            execution.cancelled_order_ids.push(cancelled.id);
+        }
+    } else if maker_cut > Decimal::ZERO {
+        let quantity = maker.quantity - maker_cut;
+        if let Some(maker) = level.update(seq, |maker| maker.reduce_to(quantity)) {
+            execution.reports.push(ExecutionReport::new(maker, ReportKind::Replaced));
+            execution.reduced_order_ids.push(maker.id.clone());
+        }
+    }
+
+    if taker_cut > Decimal::ZERO {
+        taker.quantity -= taker_cut;
+        execution.cancelled_quantity += taker_cut;
+        let kind = if taker.quantity.is_zero() {
+            ReportKind::Cancelled
+        } else {
+            ReportKind::Replaced
+        };
+        execution.reports.push(ExecutionReport::new(taker, kind));
+    }
+    true
+}
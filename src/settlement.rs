@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+
+use crate::account::AccountLedger;
+use crate::error::{OrderError, TokenError};
+use crate::order::Side;
+use crate::token::{TokenLedger, ZERO_ADDRESS};
+use crate::trade::Trade;
+
+/// What one account receives (positive) or pays (negative) of one asset
+/// once a batch is netted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetPosition {
+    pub(crate) account: String,
+    pub(crate) asset: String,
+    pub(crate) amount: Decimal,
+}
+
+impl NetPosition {
+    pub fn account(&self) -> &str {
+        &self.account
+    }
+
+    pub fn asset(&self) -> &str {
+        &self.asset
+    }
+
+    /// Net amount, positive for a credit and negative for a debit.
+    pub fn amount(&self) -> Decimal {
+        self.amount
+    }
+}
+
+/// Outcome of a settled batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementReport {
+    pub(crate) trade_count: usize,
+    pub(crate) gross_transfers: usize,
+    pub(crate) positions: Vec<NetPosition>,
+}
+
+impl SettlementReport {
+    pub fn trade_count(&self) -> usize {
+        self.trade_count
+    }
+
+    /// Transfers settling each trade on its own would take: the buyer's
+    /// payment and the seller's delivery.
+    pub fn gross_transfers(&self) -> usize {
+        self.gross_transfers
+    }
+
+    /// Transfers the netted batch took, one per non-zero position.
+    pub fn net_transfers(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Every non-zero position, by account then asset.
+    pub fn positions(&self) -> &[NetPosition] {
+        &self.positions
+    }
+}
+
+/// Trades collected for settlement. Each account's debits and credits are
+/// netted per asset, so a batch moves every (account, asset) pair at most
+/// once however many trades it holds, and is applied all or nothing.
+///
+/// Batches settle trades nothing else has paid for: those of a plain
+/// `OrderBook`, a dark pool, or an engine instrument without assets. Trades
+/// on a funded engine instrument were already paid out of their orders'
+/// reservations and are refused.
+#[derive(Debug, Clone, Default)]
+pub struct SettlementBatch {
+    net: BTreeMap<(String, String), Decimal>,  // Running net amount by (account, asset)
+    trade_count: usize,
+}
+
+impl SettlementBatch {
+    pub fn new() -> Self {
+        SettlementBatch::default()
+    }
+
+    /// Adds a trade of an instrument exchanging `base_asset` for
+    /// `quote_asset`. Both orders must have carried an account, and the
+    /// engine must not have settled the trade already.
+    pub fn add_trade(
+        &mut self,
+        base_asset: &str,
+        quote_asset: &str,
+        trade: &Trade,
+    ) -> Result<(), OrderError> {
+        if trade.is_settled() {
+            return Err(OrderError::TradeAlreadySettled(trade.id()));
+        }
+        let maker = trade
+            .maker_account()
+            .ok_or_else(|| OrderError::MissingAccount(trade.maker_order_id().to_string()))?;
+        let taker = trade
+            .taker_account()
+            .ok_or_else(|| OrderError::MissingAccount(trade.taker_order_id().to_string()))?;
+        let (buyer, seller) = match trade.aggressor() {
+            Side::Bid => (taker, maker),
+            Side::Ask => (maker, taker),
+        };
+        let cost = trade.price() * trade.quantity();
+        self.add(buyer, base_asset, trade.quantity());
+        self.add(buyer, quote_asset, -cost);
+        self.add(seller, base_asset, -trade.quantity());
+        self.add(seller, quote_asset, cost);
+        self.trade_count += 1;
+        Ok(())
+    }
+
+    /// Adds every trade in `trades`, stopping at the first one refused.
+    pub fn add_trades<'a>(
+        &mut self,
+        base_asset: &str,
+        quote_asset: &str,
+        trades: impl IntoIterator<Item = &'a Trade>,
+    ) -> Result<(), OrderError> {
+        for trade in trades {
+            self.add_trade(base_asset, quote_asset, trade)?;
+        }
+        Ok(())
+    }
+
+    pub fn trade_count(&self) -> usize {
+        self.trade_count
+    }
+
+    /// Net positions so far, by account then asset, leaving out those that
+    /// cancelled out.
+    pub fn positions(&self) -> Vec<NetPosition> {
+        self.net
+            .iter()
+            .filter(|(_, amount)| !amount.is_zero())
+            .map(|((account, asset), amount)| NetPosition {
+                account: account.clone(),
+                asset: asset.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    /// Applies the batch to available balances in `ledger`. If any account
+    /// can't cover its net debit, nothing is applied.
+    pub fn settle(self, ledger: &mut AccountLedger) -> Result<SettlementReport, OrderError> {
+        let positions = self.positions();
+        for position in positions.iter().filter(|position| position.amount < Decimal::ZERO) {
+            let available = ledger.balance(&position.account, &position.asset).available();
+            if available < -position.amount {
+                return Err(OrderError::InsufficientFunds {
+                    account: position.account.clone(),
+                    asset: position.asset.clone(),
+                    required: -position.amount,
+                    available,
+                });
+            }
+        }
+        for position in &positions {
+            if position.amount > Decimal::ZERO {
+                ledger.credit(&position.account, &position.asset, position.amount);
+            } else {
+                ledger.debit(&position.account, &position.asset, -position.amount);
+            }
+        }
+        Ok(self.into_report(positions))
+    }
+
+    /// Applies the batch on token ledgers the way a settlement contract at
+    /// `clearing` would: it pulls every net debit with `transfer_from`, which
+    /// needs an allowance for `clearing`, then pays every net credit with
+    /// `transfer`. `tokens` must hold a ledger for every asset, matched by
+    /// symbol. If any pull would revert, nothing is applied.
+    pub fn settle_tokens(
+        self,
+        tokens: &mut [TokenLedger],
+        clearing: &str,
+    ) -> Result<SettlementReport, TokenError> {
+        let positions = self.positions();
+        let mut transfers = Vec::with_capacity(positions.len());
+        for position in &positions {
+            let token = tokens
+                .iter()
+                .position(|token| token.symbol() == position.asset)
+                .ok_or_else(|| TokenError::UnknownToken(position.asset.clone()))?;
+            let units = tokens[token].to_units(position.amount.abs())?;
+            if position.amount < Decimal::ZERO {
+                tokens[token].check_transfer_from(clearing, &position.account, units)?;
+            } else if position.account == ZERO_ADDRESS {
+                return Err(TokenError::InvalidReceiver(position.account.clone()));
+            }
+            transfers.push((token, units));
+        }
+
+        // Pull every debit first so the clearing address can pay the credits
+        let (debits, credits): (Vec<_>, Vec<_>) = positions
+            .iter()
+            .zip(transfers)
+            .partition(|(position, _)| position.amount < Decimal::ZERO);
+        for (position, (token, units)) in debits {
+            tokens[token]
+                .transfer_from(clearing, &position.account, clearing, units)
+                .expect("pull was checked");
+        }
+        for (position, (token, units)) in credits {
+            tokens[token]
+                .transfer(clearing, &position.account, units)
+                .expect("a balanced batch leaves clearing enough to pay");
+        }
+        Ok(self.into_report(positions))
+    }
+
+    fn add(&mut self, account: &str, asset: &str, amount: Decimal) {
+        *self.net.entry((account.to_string(), asset.to_string())).or_default() += amount;
+    }
+
+    fn into_report(self, positions: Vec<NetPosition>) -> SettlementReport {
+        SettlementReport {
+            trade_count: self.trade_count,
+            gross_transfers: self.trade_count * 2,
+            positions,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use rust_decimal::Decimal;
+
+use crate::auction::MarketMode;
+use crate::instrument::InstrumentSpec;
+use crate::order::Order;
+
+/// Level-3 picture of a book: every resting order in queue order, plus the
+/// stop orders waiting for their trigger. `OrderBook::from_snapshot` rebuilds
+/// an identical book from it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BookSnapshot {
+    pub(crate) sequence: u64,         // Last `BookDelta` already reflected
+    pub(crate) engine_sequence: u64,  // Last book command already reflected
+    pub(crate) last_trade_id: u64,
+    pub(crate) clock: u64,
+    pub(crate) spec: InstrumentSpec,
+    pub(crate) market_mode: MarketMode,
+    pub(crate) last_price: Option<Decimal>,
+    pub(crate) bids: Vec<Order>,   // Best price first, queue order within a price
+    pub(crate) asks: Vec<Order>,   // Best price first, queue order within a price
+    pub(crate) stops: Vec<Order>,  // Trigger order
+}
+
+impl BookSnapshot {
+    /// Assembles a snapshot from exported orders, for example after reloading
+    /// them from storage. Each list must already be in the order its accessor
+    /// documents; book counters start at zero unless set with the `with_`
+    /// methods.
+    pub fn new(bids: Vec<Order>, asks: Vec<Order>, stops: Vec<Order>) -> Self {
+        BookSnapshot {
+            bids,
+            asks,
+            stops,
+            ..BookSnapshot::default()
+        }
+    }
+
+    pub fn with_sequence(mut self, sequence: u64) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    pub fn with_engine_sequence(mut self, engine_sequence: u64) -> Self {
+        self.engine_sequence = engine_sequence;
+        self
+    }
+
+    pub fn with_last_trade_id(mut self, last_trade_id: u64) -> Self {
+        self.last_trade_id = last_trade_id;
+        self
+    }
+
+    pub fn with_clock(mut self, clock: u64) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_spec(mut self, spec: InstrumentSpec) -> Self {
+        self.spec = spec;
+        self
+    }
+
+    pub fn with_market_mode(mut self, market_mode: MarketMode) -> Self {
+        self.market_mode = market_mode;
+        self
+    }
+
+    pub fn with_last_price(mut self, last_price: Decimal) -> Self {
+        self.last_price = Some(last_price);
+        self
+    }
+
+    /// Sequence of the last book delta the snapshot includes. Deltas with a
+    /// higher sequence apply on top of it.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn engine_sequence(&self) -> u64 {
+        self.engine_sequence
+    }
+
+    pub fn last_trade_id(&self) -> u64 {
+        self.last_trade_id
+    }
+
+    /// Book time when the snapshot was taken.
+    pub fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    pub fn spec(&self) -> &InstrumentSpec {
+        &self.spec
+    }
+
+    pub fn market_mode(&self) -> MarketMode {
+        self.market_mode
+    }
+
+    pub fn last_price(&self) -> Option<Decimal> {
+        self.last_price
+    }
+
+    /// Resting bids, best price first and in queue order within a price.
+    pub fn bids(&self) -> &[Order] {
+        &self.bids
+    }
+
+    /// Resting asks, best price first and in queue order within a price.
+    pub fn asks(&self) -> &[Order] {
+        &self.asks
+    }
+
+    /// Pending stop orders in the order they would trigger.
+    pub fn stops(&self) -> &[Order] {
+        &self.stops
+    }
+}
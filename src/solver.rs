@@ -0,0 +1,349 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use rust_decimal::Decimal;
+
+use crate::auction::MarketMode;
+use crate::book::OrderBook;
+use crate::error::SolutionError;
+use crate::intent::{Intent, IntentFill, SettlementPlan};
+use crate::order::{Order, Side};
+use crate::ring::RingSolver;
+
+/// A strategy that proposes how to settle a batch of intents: which to
+/// fill, by how much, and the clearing price of every token traded.
+pub trait Solver: Send + Sync {
+    /// Identifies the solver in competition results.
+    fn name(&self) -> &str;
+
+    /// Proposes a settlement for `intents`. The plan is checked before it
+    /// can win, so it may leave intents out but must never overfill one.
+    fn solve(&self, intents: &[Intent]) -> SettlementPlan;
+}
+
+impl Solver for RingSolver {
+    fn name(&self) -> &str {
+        "ring"
+    }
+
+    fn solve(&self, intents: &[Intent]) -> SettlementPlan {
+        RingSolver::solve(self, intents)
+    }
+}
+
+/// The naive strategy: every token pair is cleared on its own in a batch
+/// auction `OrderBook`, so only direct swaps between two tokens trade.
+///
+/// Intents selling the pair's lower-named token become asks at their limit
+/// price; those buying it become bids for exactly the amount they asked
+/// for. Pairs are visited by name, and a pair whose tokens were both priced
+/// by earlier pairs is skipped, since its own clearing price would
+/// contradict theirs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BookSolver;
+
+impl Solver for BookSolver {
+    fn name(&self) -> &str {
+        "book"
+    }
+
+    fn solve(&self, intents: &[Intent]) -> SettlementPlan {
+        let mut pairs: BTreeMap<(&str, &str), Vec<usize>> = BTreeMap::new();
+        for (index, intent) in intents.iter().enumerate() {
+            if !intent.is_valid() {
+                continue;
+            }
+            let (sell, buy) = (intent.sell_token.as_str(), intent.buy_token.as_str());
+            pairs.entry((sell.min(buy), sell.max(buy))).or_default().push(index);
+        }
+
+        let mut plan = SettlementPlan::default();
+        for ((base, quote), members) in pairs {
+            let base_price = plan.price(base);
+            let quote_price = plan.price(quote);
+            if base_price.is_some() && quote_price.is_some() {
+                continue;
+            }
+            let Some((price, fills)) = cross(intents, base, &members) else {
+                continue;
+            };
+            let (base_price, quote_price) = match (base_price, quote_price) {
+                (Some(base_price), None) => (base_price, base_price / price),
+                (None, Some(quote_price)) => (quote_price * price, quote_price),
+                _ => (price, Decimal::ONE),
+            };
+            plan.prices.insert(base.to_string(), base_price);
+            plan.prices.insert(quote.to_string(), quote_price);
+            plan.fills.extend(fills);
+        }
+        plan
+    }
+}
+
+/// Clears one pair's intents in a batch auction. Returns the clearing
+/// price, in quote per base, and the fills, or `None` if nothing trades or
+/// rounding would push a fill past its limit.
+fn cross(intents: &[Intent], base: &str, members: &[usize]) -> Option<(Decimal, Vec<IntentFill>)> {
+    let mut book = OrderBook::new();
+    book.set_market_mode(MarketMode::BatchAuction(1));
+    for &index in members {
+        let intent = &intents[index];
+        let order = if intent.sell_token == base {
+            // Round the ask up and the bid down so neither trades past its limit
+            let mut price = intent.buy_amount / intent.sell_amount;
+            if price * intent.sell_amount < intent.buy_amount {
+                price += Decimal::new(1, price.scale());
+            }
+            Order::new(index.to_string(), Side::Ask, price, intent.sell_amount, 0)
+        } else {
+            let mut price = intent.sell_amount / intent.buy_amount;
+            if price * intent.buy_amount > intent.sell_amount {
+                price -= Decimal::new(1, price.scale());
+            }
+            Order::new(index.to_string(), Side::Bid, price, intent.buy_amount, 0)
+        };
+        // Orders the book refuses, such as a bid rounded down to zero, sit out
+        book.add_order(order).ok();
+    }
+
+    let execution = book.run_auction(1);
+    let price = execution.trades().first()?.price();
+    let mut executed: BTreeMap<usize, (Decimal, Decimal)> = BTreeMap::new();  // Sold, bought
+    for trade in execution.trades() {
+        let (bid, ask) = match trade.aggressor() {
+            Side::Bid => (trade.taker_order_id(), trade.maker_order_id()),
+            Side::Ask => (trade.maker_order_id(), trade.taker_order_id()),
+        };
+        let index = |id: &str| id.parse::<usize>().expect("orders are keyed by intent index");
+        let quote = trade.quantity() * trade.price();
+        let ask = executed.entry(index(ask)).or_default();
+        ask.0 += trade.quantity();
+        ask.1 += quote;
+        let bid = executed.entry(index(bid)).or_default();
+        bid.0 += quote;
+        bid.1 += trade.quantity();
+    }
+
+    let mut fills = Vec::with_capacity(executed.len());
+    for (index, (sold, bought)) in executed {
+        let intent = &intents[index];
+        if sold > intent.sell_amount || !intent.accepts(sold, bought) {
+            return None;
+        }
+        fills.push(IntentFill::new(intent, sold, bought));
+    }
+    Some((price, fills))
+}
+
+/// How far, relative to what the clearing prices give, a fill may be off
+/// them: room for rounding in the last digits of prices derived by division.
+const PRICE_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 12);
+
+/// One solver's entry in a competition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Submission {
+    pub(crate) solver: String,
+    pub(crate) plan: SettlementPlan,
+    pub(crate) score: Result<Decimal, SolutionError>,
+}
+
+impl Submission {
+    pub fn solver(&self) -> &str {
+        &self.solver
+    }
+
+    pub fn plan(&self) -> &SettlementPlan {
+        &self.plan
+    }
+
+    /// Total user surplus valued at the competition's reference prices, or
+    /// why the plan was rejected.
+    pub fn score(&self) -> Result<Decimal, &SolutionError> {
+        self.score.as_ref().copied()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.score.is_ok()
+    }
+}
+
+/// Outcome of `SolverCompetition::run`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompetitionResult {
+    pub(crate) submissions: Vec<Submission>,  // In solver registration order
+    pub(crate) winner: Option<usize>,
+}
+
+impl CompetitionResult {
+    pub fn submissions(&self) -> &[Submission] {
+        &self.submissions
+    }
+
+    /// The valid, non-empty plan with the highest score. Ties go to the
+    /// solver registered first.
+    pub fn winner(&self) -> Option<&Submission> {
+        self.winner.map(|index| &self.submissions[index])
+    }
+}
+
+/// Runs several solvers on the same batch of intents, checks every plan
+/// they propose and picks the one that gives users the most surplus.
+///
+/// A plan must fill only intents in the batch, each at most once, within
+/// its amount and at or above its limit price, and must conserve every
+/// token: as much of it sold as bought. Every fill must also trade at the
+/// plan's uniform clearing prices, buying `sold * price(sell) / price(buy)`
+/// to within one part in 10^12. Each fill's surplus is in its buy
+/// token, so the competition values it at a reference price per token to
+/// make plans comparable; a plan leaving surplus in a token without one is
+/// rejected.
+#[derive(Default)]
+pub struct SolverCompetition {
+    solvers: Vec<Box<dyn Solver>>,
+    reference_prices: HashMap<String, Decimal>,
+}
+
+impl SolverCompetition {
+    pub fn new() -> Self {
+        SolverCompetition::default()
+    }
+
+    pub fn with_solver(mut self, solver: impl Solver + 'static) -> Self {
+        self.add_solver(solver);
+        self
+    }
+
+    /// Sets the value of one unit of `token` when scoring surplus.
+    pub fn with_reference_price(mut self, token: impl Into<String>, price: Decimal) -> Self {
+        self.set_reference_price(token, price);
+        self
+    }
+
+    pub fn add_solver(&mut self, solver: impl Solver + 'static) {
+        self.solvers.push(Box::new(solver));
+    }
+
+    pub fn set_reference_price(&mut self, token: impl Into<String>, price: Decimal) {
+        self.reference_prices.insert(token.into(), price);
+    }
+
+    pub fn reference_price(&self, token: &str) -> Option<Decimal> {
+        self.reference_prices.get(token).copied()
+    }
+
+    pub fn run(&self, intents: &[Intent]) -> CompetitionResult {
+        let submissions: Vec<Submission> = self
+            .solvers
+            .iter()
+            .map(|solver| {
+                let plan = solver.solve(intents);
+                let score = self.score(intents, &plan);
+                Submission {
+                    solver: solver.name().to_string(),
+                    plan,
+                    score,
+                }
+            })
+            .collect();
+
+        let mut winner: Option<(usize, Decimal)> = None;
+        for (index, submission) in submissions.iter().enumerate() {
+            let Ok(score) = submission.score else {
+                continue;
+            };
+            if !submission.plan.is_empty() && winner.is_none_or(|(_, best)| score > best) {
+                winner = Some((index, score));
+            }
+        }
+        CompetitionResult {
+            submissions,
+            winner: winner.map(|(index, _)| index),
+        }
+    }
+
+    /// Checks `plan` against `intents` and values the surplus it gives.
+    pub fn score(&self, intents: &[Intent], plan: &SettlementPlan) -> Result<Decimal, SolutionError> {
+        let mut by_id: HashMap<&str, &Intent> = HashMap::new();
+        for intent in intents {
+            by_id.entry(intent.id.as_str()).or_insert(intent);
+        }
+
+        let mut filled = HashSet::new();
+        let mut flows: BTreeMap<&str, (Decimal, Decimal)> = BTreeMap::new();  // Sold, bought per token
+        let mut score = Decimal::ZERO;
+        for fill in &plan.fills {
+            let id = fill.intent_id.as_str();
+            let intent = by_id.get(id).ok_or_else(|| SolutionError::UnknownIntent(id.to_string()))?;
+            if !filled.insert(id) {
+                return Err(SolutionError::DuplicateFill(id.to_string()));
+            }
+            if fill.account != intent.account
+                || fill.sell_token != intent.sell_token
+                || fill.buy_token != intent.buy_token
+            {
+                return Err(SolutionError::MismatchedFill(id.to_string()));
+            }
+            if !intent.is_valid() || fill.sold <= Decimal::ZERO || fill.bought <= Decimal::ZERO {
+                return Err(SolutionError::InvalidFill {
+                    intent: id.to_string(),
+                    sold: fill.sold,
+                    bought: fill.bought,
+                });
+            }
+            if fill.sold > intent.sell_amount {
+                return Err(SolutionError::Overfill {
+                    intent: id.to_string(),
+                    sold: fill.sold,
+                    available: intent.sell_amount,
+                });
+            }
+            if !intent.accepts(fill.sold, fill.bought) {
+                return Err(SolutionError::LimitPriceViolated {
+                    intent: id.to_string(),
+                    sold: fill.sold,
+                    bought: fill.bought,
+                });
+            }
+            let price = |token: &String| {
+                plan.price(token)
+                    .filter(|price| *price > Decimal::ZERO)
+                    .ok_or_else(|| SolutionError::MissingPrice(token.clone()))
+            };
+            let (sell_price, buy_price) = (price(&fill.sell_token)?, price(&fill.buy_token)?);
+            let expected = fill
+                .sold
+                .checked_mul(sell_price)
+                .and_then(|value| value.checked_div(buy_price))
+                .ok_or_else(|| SolutionError::InvalidFill {
+                    intent: id.to_string(),
+                    sold: fill.sold,
+                    bought: fill.bought,
+                })?;
+            if (fill.bought - expected).abs() > expected * PRICE_TOLERANCE {
+                return Err(SolutionError::OffPrice {
+                    intent: id.to_string(),
+                    bought: fill.bought,
+                    expected,
+                });
+            }
+
+            flows.entry(&fill.sell_token).or_default().0 += fill.sold;
+            flows.entry(&fill.buy_token).or_default().1 += fill.bought;
+            // Recomputed from the batch's own intent rather than trusted
+            let surplus = fill.bought - fill.sold * intent.limit_price();
+            let reference = self
+                .reference_price(&fill.buy_token)
+                .ok_or_else(|| SolutionError::NoReferencePrice(fill.buy_token.clone()))?;
+            score += surplus * reference;
+        }
+
+        for (token, (sold, bought)) in flows {
+            if sold != bought {
+                return Err(SolutionError::Unbalanced {
+                    token: token.to_string(),
+                    sold,
+                    bought,
+                });
+            }
+        }
+        Ok(score)
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::{BTreeMap, HashMap};
+use rust_decimal::Decimal;
+
+use crate::level::{OrderLocation, PriceLevel};
+use crate::order::{Order, Side};
+
+/// Stop and stop-limit orders waiting for their trigger, keyed by stop price.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct StopBook {
+    buys: BTreeMap<Decimal, PriceLevel>,    // Trigger once the last price rises to the key
+    sells: BTreeMap<Decimal, PriceLevel>,   // Trigger once the last price falls to the key
+    index: HashMap<String, OrderLocation>,  // Every pending stop by id
+}
+
+impl StopBook {
+    pub(crate) fn insert(&mut self, order: Order) {
+        let id = order.id.clone();
+        let side = order.side;
+        let price = order.stop_price.expect("stop orders carry a stop price");
+        let seq = self.levels_mut(side).entry(price).or_default().push_back(order);
+        self.index.insert(id, OrderLocation { side, price, seq });
+    }
+
+    pub(crate) fn get(&self, id: &str) -> Option<&Order> {
+        let location = self.index.get(id)?;
+        let levels = match location.side {
+            Side::Bid => &self.buys,
+            Side::Ask => &self.sells,
+        };
+        levels.get(&location.price)?.get(location.seq)
+    }
+
+    pub(crate) fn contains(&self, id: &str) -> bool {
+        self.index.contains_key(id)
+    }
+
+    pub(crate) fn remove(&mut self, id: &str) -> Option<Order> {
+        let location = self.index.remove(id)?;
+        let levels = match location.side {
+            Side::Bid => &mut self.buys,
+            Side::Ask => &mut self.sells,
+        };
+        let level = levels.get_mut(&location.price)?;
+        let order = level.take(location.seq);
+        if level.is_empty() {
+            levels.remove(&location.price);
+        } else if level.is_sparse() {
+            level.compact(&mut self.index);
+        }
+        order
+    }
+
+    /// Removes and returns the next stop set off by `last_price`. Buy stops
+    /// go first, lowest stop price first; then sell stops, highest first;
+    /// orders sharing a stop price trigger in arrival order.
+    pub(crate) fn pop_triggered(&mut self, last_price: Decimal) -> Option<Order> {
+        let mut entry = match self.buys.first_entry() {
+            Some(entry) if *entry.key() <= last_price => entry,
+            _ => match self.sells.last_entry() {
+                Some(entry) if *entry.key() >= last_price => entry,
+                _ => return None,
+            },
+        };
+        let order = entry.get_mut().pop_front();
+        if entry.get().is_empty() {
+            entry.remove();
+        }
+        if let Some(order) = &order {
+            self.index.remove(&order.id);
+        }
+        order
+    }
+
+    /// Pending stops in trigger order: buys by rising stop price, then sells
+    /// by falling stop price.
+    pub(crate) fn orders(&self) -> impl Iterator<Item = &Order> {
+        let buys = self.buys.values().flat_map(|level| level.entries());
+        let sells = self.sells.values().rev().flat_map(|level| level.entries());
+        buys.chain(sells).map(|(_, order)| order)
+    }
+
+    fn levels_mut(&mut self, side: Side) -> &mut BTreeMap<Decimal, PriceLevel> {
+        match side {
+            Side::Bid => &mut self.buys,
+            Side::Ask => &mut self.sells,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::HashMap;
+use rust_decimal::Decimal;
+
+use crate::error::TokenError;
+
+/// The address mints come from and burns go to. It can never hold tokens.
+pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Log entries a token emits, as ERC-20 defines them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenEvent {
+    Transfer { from: String, to: String, value: u128 },         // `from` is the zero address for mints, `to` for burns
+    Approval { owner: String, spender: String, value: u128 },  // `value` is the new allowance
+}
+
+/// An in-process ERC-20 token. Amounts are integers in the token's smallest
+/// unit, as on chain; `to_units` and `from_units` convert from and to
+/// human amounts using the token's decimals.
+///
+/// Calls return `Err` where the contract would revert and leave the ledger
+/// untouched. Allowances behave like OpenZeppelin's: an allowance of
+/// `u128::MAX` is infinite and never decreases.
+#[derive(Debug, Clone)]
+pub struct TokenLedger {
+    name: String,
+    symbol: String,
+    decimals: u8,
+    total_supply: u128,
+    balances: HashMap<String, u128>,
+    allowances: HashMap<(String, String), u128>,  // By (owner, spender)
+    events: Vec<TokenEvent>,                      // Waiting for `drain_events`
+}
+
+impl TokenLedger {
+    /// Creates a token with no supply. `decimals` is capped at 28, the most a
+    /// `Decimal` amount can carry.
+    pub fn new(name: impl Into<String>, symbol: impl Into<String>, decimals: u8) -> Self {
+        TokenLedger {
+            name: name.into(),
+            symbol: symbol.into(),
+            decimals: decimals.min(28),
+            total_supply: 0,
+            balances: HashMap::new(),
+            allowances: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    pub fn decimals(&self) -> u8 {
+        self.decimals
+    }
+
+    pub fn total_supply(&self) -> u128 {
+        self.total_supply
+    }
+
+    pub fn balance_of(&self, owner: &str) -> u128 {
+        self.balances.get(owner).copied().unwrap_or(0)
+    }
+
+    pub fn allowance(&self, owner: &str, spender: &str) -> u128 {
+        self.allowances
+            .get(&(owner.to_string(), spender.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Moves `value` from `sender`, the caller, to `to`.
+    pub fn transfer(&mut self, sender: &str, to: &str, value: u128) -> Result<(), TokenError> {
+        self.move_tokens(sender, to, value)
+    }
+
+    /// Lets `spender` move up to `value` of `owner`'s tokens, replacing any
+    /// previous allowance.
+    pub fn approve(&mut self, owner: &str, spender: &str, value: u128) -> Result<(), TokenError> {
+        if owner == ZERO_ADDRESS {
+            return Err(TokenError::InvalidApprover(owner.to_string()));
+        }
+        if spender == ZERO_ADDRESS {
+            return Err(TokenError::InvalidSpender(spender.to_string()));
+        }
+        self.allowances.insert((owner.to_string(), spender.to_string()), value);
+        self.events.push(TokenEvent::Approval {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Moves `value` from `from` to `to` on behalf of `spender`, the caller,
+    /// out of the allowance `from` gave it.
+    pub fn transfer_from(
+        &mut self,
+        spender: &str,
+        from: &str,
+        to: &str,
+        value: u128,
+    ) -> Result<(), TokenError> {
+        self.check_allowance(spender, from, value)?;
+        self.move_tokens(from, to, value)?;
+        self.spend_allowance(spender, from, value)
+    }
+
+    /// Creates `value` new tokens for `to`.
+    pub fn mint(&mut self, to: &str, value: u128) -> Result<(), TokenError> {
+        if to == ZERO_ADDRESS {
+            return Err(TokenError::InvalidReceiver(to.to_string()));
+        }
+        self.total_supply = self.total_supply.checked_add(value).ok_or(TokenError::SupplyOverflow)?;
+        *self.balances.entry(to.to_string()).or_default() += value;
+        self.events.push(TokenEvent::Transfer {
+            from: ZERO_ADDRESS.to_string(),
+            to: to.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Destroys `value` of `from`'s tokens.
+    pub fn burn(&mut self, from: &str, value: u128) -> Result<(), TokenError> {
+        if from == ZERO_ADDRESS {
+            return Err(TokenError::InvalidSender(from.to_string()));
+        }
+        self.debit(from, value)?;
+        self.total_supply -= value;
+        self.events.push(TokenEvent::Transfer {
+            from: from.to_string(),
+            to: ZERO_ADDRESS.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    /// Takes the events emitted since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<TokenEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Converts a human amount, e.g. 1.5, into token units.
+    pub fn to_units(&self, amount: Decimal) -> Result<u128, TokenError> {
+        let mut units = amount;
+        units.rescale(self.decimals as u32);
+        // Rescaling rounds away extra precision and stops short on overflow
+        if units != amount || units.scale() != self.decimals as u32 || units.is_sign_negative() {
+            return Err(TokenError::UnrepresentableAmount(amount));
+        }
+        u128::try_from(units.mantissa()).map_err(|_| TokenError::UnrepresentableAmount(amount))
+    }
+
+    /// Converts token units into a human amount, `None` if it is too large
+    /// for a `Decimal`.
+    pub fn from_units(&self, units: u128) -> Option<Decimal> {
+        let units = i128::try_from(units).ok()?;
+        Decimal::try_from_i128_with_scale(units, self.decimals as u32).ok()
+    }
+
+    /// Whether `spender` could pull `value` from `from` right now, without
+    /// doing it.
+    pub(crate) fn check_transfer_from(&self, spender: &str, from: &str, value: u128) -> Result<(), TokenError> {
+        if from == ZERO_ADDRESS {
+            return Err(TokenError::InvalidSender(from.to_string()));
+        }
+        self.check_allowance(spender, from, value)?;
+        let balance = self.balance_of(from);
+        if balance < value {
+            return Err(TokenError::InsufficientBalance {
+                owner: from.to_string(),
+                balance,
+                needed: value,
+            });
+        }
+        Ok(())
+    }
+
+    /// Uses up `value` of the allowance `owner` gave `spender`, without an
+    /// `Approval` event.
+    pub(crate) fn spend_allowance(&mut self, spender: &str, owner: &str, value: u128) -> Result<(), TokenError> {
+        let allowance = self.check_allowance(spender, owner, value)?;
+        if allowance != u128::MAX {
+            self.allowances.insert((owner.to_string(), spender.to_string()), allowance - value);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn check_allowance(&self, spender: &str, from: &str, value: u128) -> Result<u128, TokenError> {
+        let allowance = self.allowance(from, spender);
+        if allowance < value {
+            return Err(TokenError::InsufficientAllowance {
+                owner: from.to_string(),
+                spender: spender.to_string(),
+                allowance,
+                needed: value,
+            });
+        }
+        Ok(allowance)
+    }
+
+    fn move_tokens(&mut self, from: &str, to: &str, value: u128) -> Result<(), TokenError> {
+        if from == ZERO_ADDRESS {
+            return Err(TokenError::InvalidSender(from.to_string()));
+        }
+        if to == ZERO_ADDRESS {
+            return Err(TokenError::InvalidReceiver(to.to_string()));
+        }
+        self.debit(from, value)?;
+        *self.balances.entry(to.to_string()).or_default() += value;
+        self.events.push(TokenEvent::Transfer {
+            from: from.to_string(),
+            to: to.to_string(),
+            value,
+        });
+        Ok(())
+    }
+
+    fn debit(&mut self, owner: &str, value: u128) -> Result<(), TokenError> {
+        let balance = self.balance_of(owner);
+        if balance < value {
+            return Err(TokenError::InsufficientBalance {
+                owner: owner.to_string(),
+                balance,
+                needed: value,
+            });
+        }
+        self.balances.insert(owner.to_string(), balance - value);
+        Ok(())
+    }
+}
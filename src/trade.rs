@@ -0,0 +1,149 @@
+use rust_decimal::Decimal;
+
+use crate::order::{Order, Side};
+use crate::report::ExecutionReport;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub(crate) id: u64,  // Increases by one per trade within a book and its dark pool
+    pub(crate) maker_order_id: String,
+    pub(crate) taker_order_id: String,
+    pub(crate) maker_account: Option<String>,
+    pub(crate) taker_account: Option<String>,
+    pub(crate) aggressor: Side,
+    pub(crate) price: Decimal,
+    pub(crate) quantity: Decimal,
+    pub(crate) maker_remaining: Decimal,
+    pub(crate) taker_remaining: Decimal,
+    pub(crate) timestamp: u64,
+    pub(crate) sequence: u64,
+    pub(crate) settled: bool,  // Paid out of reservations by a funded engine
+}
+
+impl Trade {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn maker_order_id(&self) -> &str {
+        &self.maker_order_id
+    }
+
+    pub fn taker_order_id(&self) -> &str {
+        &self.taker_order_id
+    }
+
+    pub fn maker_account(&self) -> Option<&str> {
+        self.maker_account.as_deref()
+    }
+
+    pub fn taker_account(&self) -> Option<&str> {
+        self.taker_account.as_deref()
+    }
+
+    /// Execution price: the maker's resting price in continuous matching,
+    /// the uniform clearing price in a batch auction, and the lit midpoint
+    /// for a dark pool cross.
+    pub fn price(&self) -> Decimal {
+        self.price
+    }
+
+    pub fn quantity(&self) -> Decimal {
+        self.quantity
+    }
+
+    /// Side of the incoming order that took liquidity.
+    pub fn aggressor(&self) -> Side {
+        self.aggressor
+    }
+
+    /// Maker's unfilled quantity after this trade, hidden reserve included.
+    pub fn maker_remaining(&self) -> Decimal {
+        self.maker_remaining
+    }
+
+    /// Taker's unfilled quantity after this trade.
+    pub fn taker_remaining(&self) -> Decimal {
+        self.taker_remaining
+    }
+
+    /// Book time of the execution.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Engine sequence of the book command that produced the trade.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Whether a `MatchingEngine` already moved the funds for this trade,
+    /// as it does for every trade on a funded instrument.
+    pub fn is_settled(&self) -> bool {
+        self.settled
+    }
+}
+
+/// Outcome of an order submitted to `OrderBook::add_order`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Execution {
+    pub(crate) trades: Vec<Trade>,
+    pub(crate) filled_quantity: Decimal,
+    pub(crate) resting_quantity: Decimal,
+    pub(crate) cancelled_quantity: Decimal,
+    pub(crate) triggered_order_ids: Vec<String>,
+    pub(crate) cancelled_order_ids: Vec<String>,
+    pub(crate) reduced_order_ids: Vec<String>,
+    pub(crate) expired_orders: Vec<Order>,
+    pub(crate) reports: Vec<ExecutionReport>,  // Handed over to the book's report queue
+}
+
+impl Execution {
+    /// Every trade the call produced, including those of stop orders it
+    /// triggered.
+    pub fn trades(&self) -> &[Trade] {
+        &self.trades
+    }
+
+    pub fn into_trades(self) -> Vec<Trade> {
+        self.trades
+    }
+
+    /// Quantity filled on the submitted order itself.
+    pub fn filled_quantity(&self) -> Decimal {
+        self.filled_quantity
+    }
+
+    /// Quantity left on the book after matching.
+    pub fn resting_quantity(&self) -> Decimal {
+        self.resting_quantity
+    }
+
+    /// Quantity that could not trade and was cancelled instead of resting,
+    /// including any taken off by self-trade prevention.
+    pub fn cancelled_quantity(&self) -> Decimal {
+        self.cancelled_quantity
+    }
+
+    /// Stop orders released into matching by this call, in release order.
+    pub fn triggered_order_ids(&self) -> &[String] {
+        &self.triggered_order_ids
+    }
+
+    /// Resting orders cancelled by self-trade prevention, and
+    /// immediate-or-cancel orders left over when a batch auction cleared.
+    pub fn cancelled_order_ids(&self) -> &[String] {
+        &self.cancelled_order_ids
+    }
+
+    /// Resting orders whose quantity self-trade prevention reduced without
+    /// cancelling them.
+    pub fn reduced_order_ids(&self) -> &[String] {
+        &self.reduced_order_ids
+    }
+
+    /// Good-till-time orders that expired as the call advanced the book clock.
+    pub fn expired_orders(&self) -> &[Order] {
+        &self.expired_orders
+    }
+}
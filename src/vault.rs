@@ -0,0 +1,307 @@
+use rust_decimal::Decimal;
+
+use crate::error::TokenError;
+use crate::token::{TokenLedger, ZERO_ADDRESS};
+
+/// Log entries a vault emits, as ERC-4626 defines them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultEvent {
+    Deposit { sender: String, owner: String, assets: u128, shares: u128 },
+    Withdraw { sender: String, receiver: String, owner: String, assets: u128, shares: u128 },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rounding {
+    Down,
+    Up,
+}
+
+/// An ERC-4626 tokenized vault following OpenZeppelin's implementation: it
+/// holds one underlying asset token and issues its own share token against
+/// it.
+///
+/// Shares are priced with a virtual offset, `10^decimals_offset` virtual
+/// shares and one virtual asset, which makes inflating the share price by
+/// donating to an empty vault unprofitable. Every conversion rounds in the
+/// vault's favour: callers get fewer shares or assets for what they bring,
+/// and pay more for what they ask for.
+///
+/// The vault owns both token ledgers so their balances always agree;
+/// `asset_mut` and `shares_mut` give access for minting, approvals and
+/// transfers outside the vault.
+#[derive(Debug, Clone)]
+pub struct Vault {
+    address: String,  // Where the vault's assets are held on the asset token
+    asset: TokenLedger,
+    shares: TokenLedger,
+    decimals_offset: u8,
+    events: Vec<VaultEvent>,  // Waiting for `drain_events`
+}
+
+impl Vault {
+    /// Creates an empty vault at `address` over `asset`, issuing shares
+    /// named `name` and `symbol`.
+    pub fn new(
+        address: impl Into<String>,
+        asset: TokenLedger,
+        name: impl Into<String>,
+        symbol: impl Into<String>,
+    ) -> Self {
+        let shares = TokenLedger::new(name, symbol, asset.decimals());
+        Vault {
+            address: address.into(),
+            asset,
+            shares,
+            decimals_offset: 0,
+            events: Vec::new(),
+        }
+    }
+
+    /// Gives shares `offset` more decimals than the asset, and as many
+    /// virtual shares. Set it before the first deposit.
+    pub fn with_decimals_offset(mut self, offset: u8) -> Self {
+        let decimals = self.asset.decimals().saturating_add(offset);
+        self.shares = TokenLedger::new(self.shares.name(), self.shares.symbol(), decimals);
+        self.decimals_offset = offset;
+        self
+    }
+
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    pub fn asset(&self) -> &TokenLedger {
+        &self.asset
+    }
+
+    pub fn asset_mut(&mut self) -> &mut TokenLedger {
+        &mut self.asset
+    }
+
+    pub fn shares(&self) -> &TokenLedger {
+        &self.shares
+    }
+
+    pub fn shares_mut(&mut self) -> &mut TokenLedger {
+        &mut self.shares
+    }
+
+    pub fn decimals_offset(&self) -> u8 {
+        self.decimals_offset
+    }
+
+    /// Underlying assets the vault holds, donations included.
+    pub fn total_assets(&self) -> u128 {
+        self.asset.balance_of(&self.address)
+    }
+
+    pub fn convert_to_shares(&self, assets: u128) -> Result<u128, TokenError> {
+        self.to_shares(assets, Rounding::Down)
+    }
+
+    pub fn convert_to_assets(&self, shares: u128) -> Result<u128, TokenError> {
+        self.to_assets(shares, Rounding::Down)
+    }
+
+    pub fn max_deposit(&self, _receiver: &str) -> u128 {
+        u128::MAX
+    }
+
+    pub fn max_mint(&self, _receiver: &str) -> u128 {
+        u128::MAX
+    }
+
+    pub fn max_withdraw(&self, owner: &str) -> Result<u128, TokenError> {
+        self.to_assets(self.shares.balance_of(owner), Rounding::Down)
+    }
+
+    pub fn max_redeem(&self, owner: &str) -> u128 {
+        self.shares.balance_of(owner)
+    }
+
+    /// Shares `deposit` would mint for `assets`, rounded down.
+    pub fn preview_deposit(&self, assets: u128) -> Result<u128, TokenError> {
+        self.to_shares(assets, Rounding::Down)
+    }
+
+    /// Assets `mint` would take for `shares`, rounded up.
+    pub fn preview_mint(&self, shares: u128) -> Result<u128, TokenError> {
+        self.to_assets(shares, Rounding::Up)
+    }
+
+    /// Shares `withdraw` would burn for `assets`, rounded up.
+    pub fn preview_withdraw(&self, assets: u128) -> Result<u128, TokenError> {
+        self.to_shares(assets, Rounding::Up)
+    }
+
+    /// Assets `redeem` would pay for `shares`, rounded down.
+    pub fn preview_redeem(&self, shares: u128) -> Result<u128, TokenError> {
+        self.to_assets(shares, Rounding::Down)
+    }
+
+    /// Pulls `assets` from `caller`, who must have approved the vault, and
+    /// mints the shares they buy to `receiver`. Returns the shares minted.
+    pub fn deposit(&mut self, caller: &str, assets: u128, receiver: &str) -> Result<u128, TokenError> {
+        let shares = self.preview_deposit(assets)?;
+        self.enter(caller, receiver, assets, shares)?;
+        Ok(shares)
+    }
+
+    /// Mints exactly `shares` to `receiver`, pulling what they cost from
+    /// `caller`. Returns the assets taken.
+    pub fn mint(&mut self, caller: &str, shares: u128, receiver: &str) -> Result<u128, TokenError> {
+        let assets = self.preview_mint(shares)?;
+        self.enter(caller, receiver, assets, shares)?;
+        Ok(assets)
+    }
+
+    /// Pays exactly `assets` to `receiver`, burning the shares they cost
+    /// from `owner`. A `caller` other than the owner spends the owner's share
+    /// allowance. Returns the shares burned.
+    pub fn withdraw(
+        &mut self,
+        caller: &str,
+        assets: u128,
+        receiver: &str,
+        owner: &str,
+    ) -> Result<u128, TokenError> {
+        let shares = self.preview_withdraw(assets)?;
+        self.exit(caller, receiver, owner, assets, shares)?;
+        Ok(shares)
+    }
+
+    /// Burns `shares` from `owner` and pays what they are worth to
+    /// `receiver`. A `caller` other than the owner spends the owner's share
+    /// allowance. Returns the assets paid.
+    pub fn redeem(
+        &mut self,
+        caller: &str,
+        shares: u128,
+        receiver: &str,
+        owner: &str,
+    ) -> Result<u128, TokenError> {
+        let assets = self.preview_redeem(shares)?;
+        self.exit(caller, receiver, owner, assets, shares)?;
+        Ok(assets)
+    }
+
+    /// The account whose custody `event` changed, and by how much as a
+    /// human amount of the asset: positive for a deposit, negative for a
+    /// withdrawal. Feed it to `AccountLedger::apply_custody`. `None` if the
+    /// amount is too large for a `Decimal`.
+    pub fn custody_change(&self, event: &VaultEvent) -> Option<(String, Decimal)> {
+        match event {
+            VaultEvent::Deposit { owner, assets, .. } => Some((owner.clone(), self.asset.from_units(*assets)?)),
+            VaultEvent::Withdraw { owner, assets, .. } => Some((owner.clone(), -self.asset.from_units(*assets)?)),
+        }
+    }
+
+    /// Takes the events emitted since the last call, oldest first.
+    pub fn drain_events(&mut self) -> Vec<VaultEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    fn enter(&mut self, caller: &str, receiver: &str, assets: u128, shares: u128) -> Result<(), TokenError> {
+        // Check the mint up front so a revert leaves the asset untouched
+        if receiver == ZERO_ADDRESS {
+            return Err(TokenError::InvalidReceiver(receiver.to_string()));
+        }
+        self.shares.total_supply().checked_add(shares).ok_or(TokenError::SupplyOverflow)?;
+        self.asset.transfer_from(&self.address, caller, &self.address, assets)?;
+        self.shares.mint(receiver, shares).expect("mint was checked");
+        self.events.push(VaultEvent::Deposit {
+            sender: caller.to_string(),
+            owner: receiver.to_string(),
+            assets,
+            shares,
+        });
+        Ok(())
+    }
+
+    fn exit(
+        &mut self,
+        caller: &str,
+        receiver: &str,
+        owner: &str,
+        assets: u128,
+        shares: u128,
+    ) -> Result<(), TokenError> {
+        if receiver == ZERO_ADDRESS {
+            return Err(TokenError::InvalidReceiver(receiver.to_string()));
+        }
+        if caller != owner {
+            self.shares.check_allowance(caller, owner, shares)?;
+        }
+        self.shares.burn(owner, shares)?;
+        if caller != owner {
+            self.shares.spend_allowance(caller, owner, shares).expect("allowance was checked");
+        }
+        self.asset
+            .transfer(&self.address, receiver, assets)
+            .expect("rounding down never pays out more than the vault holds");
+        self.events.push(VaultEvent::Withdraw {
+            sender: caller.to_string(),
+            receiver: receiver.to_string(),
+            owner: owner.to_string(),
+            assets,
+            shares,
+        });
+        Ok(())
+    }
+
+    fn virtual_shares(&self) -> Result<u128, TokenError> {
+        10u128
+            .checked_pow(self.decimals_offset as u32)
+            .and_then(|offset| self.shares.total_supply().checked_add(offset))
+            .ok_or(TokenError::ArithmeticOverflow)
+    }
+
+    fn virtual_assets(&self) -> Result<u128, TokenError> {
+        self.total_assets().checked_add(1).ok_or(TokenError::ArithmeticOverflow)
+    }
+
+    fn to_shares(&self, assets: u128, rounding: Rounding) -> Result<u128, TokenError> {
+        mul_div(assets, self.virtual_shares()?, self.virtual_assets()?, rounding)
+    }
+
+    fn to_assets(&self, shares: u128, rounding: Rounding) -> Result<u128, TokenError> {
+        mul_div(shares, self.virtual_assets()?, self.virtual_shares()?, rounding)
+    }
+}
+
+/// `a * b / denominator` with a full 256-bit intermediate product, like
+/// OpenZeppelin's `Math.mulDiv`. `denominator` must be non-zero.
+fn mul_div(a: u128, b: u128, denominator: u128, rounding: Rounding) -> Result<u128, TokenError> {
+    const LOW: u128 = u64::MAX as u128;
+    let (a_high, a_low) = (a >> 64, a & LOW);
+    let (b_high, b_low) = (b >> 64, b & LOW);
+    let low_low = a_low * b_low;
+    let low_high = a_low * b_high;
+    let high_low = a_high * b_low;
+    let middle = (low_low >> 64) + (low_high & LOW) + (high_low & LOW);
+    let low = (low_low & LOW) | (middle << 64);
+    let high = a_high * b_high + (low_high >> 64) + (high_low >> 64) + (middle >> 64);
+
+    // A quotient of 2^128 or more doesn't fit
+    if high >= denominator {
+        return Err(TokenError::ArithmeticOverflow);
+    }
+
+    // Shift-subtract long division of the 256-bit product
+    let mut remainder = high;
+    let mut quotient = 0u128;
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+        quotient <<= 1;
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1;
+        }
+    }
+
+    match rounding {
+        Rounding::Up if remainder != 0 => quotient.checked_add(1).ok_or(TokenError::ArithmeticOverflow),
+        _ => Ok(quotient),
+    }
+}
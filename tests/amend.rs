@@ -0,0 +1,60 @@
+mod common;
+
+use coincidences::{Order, OrderBook, OrderError, Side};
+
+use common::d;
+
+fn two_asks() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("2"), 2)).unwrap();
+    book
+}
+
+fn first_maker(book: &mut OrderBook) -> String {
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("1"), 5)).unwrap();
+    execution.trades()[0].maker_order_id().to_string()
+}
+
+#[test]
+fn reducing_quantity_keeps_queue_position() {
+    let mut book = two_asks();
+    let execution = book.amend_order("a1", d("10"), d("1")).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.resting_quantity(), d("1"));
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("1")));
+    assert_eq!(first_maker(&mut book), "a1");
+}
+
+#[test]
+fn increasing_quantity_loses_priority() {
+    let mut book = two_asks();
+    book.amend_order("a1", d("10"), d("3")).unwrap();
+    assert_eq!(first_maker(&mut book), "a2");
+}
+
+#[test]
+fn price_change_loses_priority_and_may_trade() {
+    let mut book = two_asks();
+    book.amend_order("a1", d("11"), d("2")).unwrap();
+    book.amend_order("a1", d("10"), d("2")).unwrap();
+    assert_eq!(first_maker(&mut book), "a2");
+
+    book.add_order(Order::new("b2", Side::Bid, d("9"), d("1"), 6)).unwrap();
+    let execution = book.amend_order("a1", d("9"), d("2")).unwrap();
+    assert_eq!(execution.trades().len(), 1);
+    assert_eq!(execution.trades()[0].taker_order_id(), "a1");
+    assert_eq!(execution.trades()[0].price(), d("9"));
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("1")));
+}
+
+#[test]
+fn invalid_amend_leaves_the_order_alone() {
+    let mut book = two_asks();
+    assert_eq!(
+        book.amend_order("nope", d("10"), d("1")),
+        Err(OrderError::OrderNotFound("nope".to_string()))
+    );
+    assert_eq!(book.amend_order("a1", d("10"), d("0")), Err(OrderError::InvalidQuantity(d("0"))));
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("2")));
+}
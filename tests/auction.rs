@@ -0,0 +1,163 @@
+mod common;
+
+use coincidences::{InstrumentSpec, MarketMode, MatchingEngine, Order, OrderBook, OrderError, OrderType, Side, TimeInForce};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn batch_book() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.set_market_mode(MarketMode::BatchAuction(100));
+    book
+}
+
+#[test]
+fn orders_collect_until_the_auction() {
+    let mut book = batch_book();
+    assert_eq!(
+        book.add_order(Order::market("m1", Side::Bid, d("1"), 1)),
+        Err(OrderError::UnsupportedOrderType(OrderType::Market))
+    );
+    book.add_order(Order::new("b1", Side::Bid, d("102"), d("5"), 1)).unwrap();
+    let execution = book.add_order(Order::new("a1", Side::Ask, d("99"), d("3"), 2)).unwrap();
+    assert!(execution.trades().is_empty());
+    // The book is left crossed until it clears
+    assert_eq!(book.best_bid().map(|level| level.price()), Some(d("102")));
+    assert_eq!(book.best_ask().map(|level| level.price()), Some(d("99")));
+    assert!(book.run_auction(99).trades().is_empty());
+}
+
+#[test]
+fn auction_clears_at_one_price_for_the_most_volume() {
+    let mut book = batch_book();
+    book.add_order(Order::new("b1", Side::Bid, d("102"), d("5"), 1)).unwrap();
+    book.add_order(Order::new("b2", Side::Bid, d("101"), d("4"), 2)).unwrap();
+    book.add_order(Order::new("b3", Side::Bid, d("101"), d("2"), 3)).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("99"), d("3"), 4)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("101"), d("5"), 5)).unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("103"), d("5"), 6).with_time_in_force(TimeInForce::Ioc))
+        .unwrap();
+
+    // 8 trades at 101: demand there is 11 and supply 8
+    let execution = book.run_auction(100);
+    assert!(execution.trades().iter().all(|trade| trade.price() == d("101")));
+    assert_eq!(execution.trades().iter().map(|trade| trade.quantity()).sum::<Decimal>(), d("8"));
+    assert_eq!(book.last_price(), Some(d("101")));
+    assert!(book.best_ask().is_none());
+
+    // b1 fills whole and the 3 left are shared 2:1 at the 101 level
+    assert!(book.order("b1").is_none());
+    assert_eq!(book.order("b2").map(Order::quantity), Some(d("2")));
+    assert_eq!(book.order("b3").map(Order::quantity), Some(d("1")));
+
+    // Immediate-or-cancel orders that don't trade in their auction go
+    assert!(book.order("a3").is_none());
+    assert_eq!(execution.cancelled_quantity(), d("5"));
+}
+
+#[test]
+fn equal_volume_prices_break_towards_the_last_trade() {
+    let mut book = batch_book();
+    book.add_order(Order::new("b1", Side::Bid, d("103"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("103"), d("1"), 2)).unwrap();
+    book.run_auction(100);
+
+    // 98 and 104 both trade 2 with no imbalance
+    book.add_order(Order::new("b2", Side::Bid, d("104"), d("2"), 101)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("98"), d("2"), 102)).unwrap();
+    let execution = book.run_auction(200);
+    assert_eq!(execution.trades().iter().map(|trade| trade.price()).collect::<Vec<_>>(), vec![d("104")]);
+}
+
+#[test]
+fn an_order_past_the_boundary_clears_the_batch_first() {
+    let mut book = batch_book();
+    book.add_order(Order::new("b1", Side::Bid, d("100"), d("2"), 150)).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("100"), d("1"), 160)).unwrap();
+    let execution = book.add_order(Order::new("a2", Side::Ask, d("100"), d("1"), 201)).unwrap();
+    assert_eq!(execution.trades().len(), 1);
+    assert_eq!(execution.trades()[0].timestamp(), 200);
+    // a2 waits for the next auction
+    assert!(book.order("a2").is_some());
+    assert_eq!(
+        OrderBook::from_snapshot(&book.snapshot()).unwrap().market_mode(),
+        MarketMode::BatchAuction(100)
+    );
+}
+
+#[test]
+fn leaving_batch_mode_clears_what_was_collected() {
+    let mut book = batch_book();
+    book.add_order(Order::new("b1", Side::Bid, d("100"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("100"), d("1"), 2)).unwrap();
+    assert_eq!(book.set_market_mode(MarketMode::Continuous).trades().len(), 1);
+    assert_eq!(book.market_mode(), MarketMode::Continuous);
+    book.add_order(Order::market("m1", Side::Bid, d("1"), 3)).unwrap();
+}
+
+/// A funded ETH-USD engine in batch auctions every 10, with alice holding
+/// 1,000 USD and bob 10 ETH.
+fn funded_batch_engine() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine
+        .add_instrument("ETH-USD", InstrumentSpec::new().with_assets("ETH", "USD"))
+        .unwrap();
+    engine.set_market_mode("ETH-USD", MarketMode::BatchAuction(10)).unwrap();
+    engine.ledger_mut().deposit("alice", "USD", d("1000")).unwrap();
+    engine.ledger_mut().deposit("bob", "ETH", d("10")).unwrap();
+    engine
+}
+
+#[test]
+fn funded_auctions_settle_at_the_clearing_price() {
+    let mut engine = funded_batch_engine();
+    engine
+        .add_order("ETH-USD", Order::new("b1", Side::Bid, d("100"), d("2"), 1).with_account("alice"))
+        .unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("a1", Side::Ask, d("90"), d("2"), 2).with_account("bob"))
+        .unwrap();
+
+    // With no last trade, equally good prices go to the lower
+    let execution = engine.run_auction("ETH-USD", 10).unwrap();
+    assert_eq!(execution.trades().iter().map(|trade| trade.price()).collect::<Vec<_>>(), vec![d("90")]);
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("820"));
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("2"));
+    assert_eq!(engine.ledger().balance("bob", "USD").available(), d("180"));
+}
+
+#[test]
+fn leftover_immediate_or_cancel_orders_release_their_funds() {
+    let mut engine = funded_batch_engine();
+    let ioc = Order::new("b1", Side::Bid, d("10"), d("1"), 1)
+        .with_account("alice")
+        .with_time_in_force(TimeInForce::Ioc);
+    engine.add_order("ETH-USD", ioc).unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("10"));
+
+    let execution = engine.run_auction("ETH-USD", 10).unwrap();
+    assert_eq!(execution.cancelled_order_ids(), &["b1".to_string()]);
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("1000"));
+}
+
+#[test]
+fn leaving_batch_mode_through_the_engine_settles_the_auction() {
+    let mut engine = funded_batch_engine();
+    engine
+        .add_order("ETH-USD", Order::new("b1", Side::Bid, d("100"), d("3"), 1).with_account("alice"))
+        .unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("a1", Side::Ask, d("100"), d("2"), 2).with_account("bob"))
+        .unwrap();
+
+    let execution = engine.set_market_mode("ETH-USD", MarketMode::Continuous).unwrap();
+    assert_eq!(execution.trades().len(), 1);
+    assert!(execution.trades()[0].is_settled());
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("100"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("700"));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("2"));
+    assert_eq!(engine.ledger().balance("bob", "USD").available(), d("200"));
+    assert_eq!(engine.ledger().balance("bob", "ETH").reserved(), d("0"));
+}
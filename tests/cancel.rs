@@ -0,0 +1,74 @@
+mod common;
+
+use coincidences::{Order, OrderBook, OrderError, Side};
+
+use common::d;
+
+#[test]
+fn cancelled_order_leaves_its_queue() {
+    let mut book = OrderBook::new();
+    for (timestamp, id) in ["a1", "a2", "a3"].into_iter().enumerate() {
+        book.add_order(Order::new(id, Side::Ask, d("10"), d("1"), timestamp as u64)).unwrap();
+    }
+    let cancelled = book.cancel_order("a2").unwrap();
+    assert_eq!(cancelled.id(), "a2");
+    assert_eq!(cancelled.quantity(), d("1"));
+    assert!(book.order("a2").is_none());
+
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("3"), 5)).unwrap();
+    let makers: Vec<&str> = execution.trades().iter().map(|trade| trade.maker_order_id()).collect();
+    assert_eq!(makers, vec!["a1", "a3"]);
+    assert_eq!(book.cancel_order("b1").unwrap().quantity(), d("1"));
+}
+
+#[test]
+fn cancelling_twice_or_an_unknown_id_fails() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.cancel_order("a1").unwrap();
+    assert_eq!(book.cancel_order("a1"), Err(OrderError::OrderNotFound("a1".to_string())));
+    assert_eq!(book.cancel_order("nope"), Err(OrderError::OrderNotFound("nope".to_string())));
+}
+
+#[test]
+fn cancelling_the_last_order_removes_the_level() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.cancel_order("a1").unwrap();
+    book.cancel_order("a2").unwrap();
+    assert!(book.best_ask().is_none());
+    assert!(book.add_order(Order::new("b1", Side::Bid, d("10"), d("3"), 2)).unwrap().trades().is_empty());
+}
+
+#[test]
+fn churn_behind_a_resting_order_keeps_lookups_and_priority() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a0", Side::Ask, d("10"), d("1"), 0)).unwrap();
+    book.add_order(Order::stop("s0", Side::Bid, d("20"), d("1"), 0)).unwrap();
+    for index in 1..=200 {
+        let id = format!("a{index}");
+        book.add_order(Order::new(id.clone(), Side::Ask, d("10"), d("1"), index)).unwrap();
+        let stop = format!("s{index}");
+        book.add_order(Order::stop(stop.clone(), Side::Bid, d("20"), d("1"), index)).unwrap();
+        // Every tenth order stays, so compaction has to move live orders
+        if index % 10 != 0 {
+            book.cancel_order(&id).unwrap();
+            book.cancel_order(&stop).unwrap();
+        }
+    }
+    for index in (10..=200).step_by(10) {
+        assert_eq!(book.order(&format!("a{index}")).map(Order::id), Some(format!("a{index}").as_str()));
+        assert!(book.order(&format!("s{index}")).is_some());
+    }
+    book.cancel_order("a30").unwrap();
+    book.cancel_order("s30").unwrap();
+    book.amend_order("a50", d("10"), d("0.5")).unwrap();
+
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("50"), 300)).unwrap();
+    let makers: Vec<&str> = execution.trades().iter().map(|trade| trade.maker_order_id()).collect();
+    let mut expected = vec!["a0".to_string()];
+    expected.extend((10..=200).step_by(10).filter(|index| *index != 30).map(|index| format!("a{index}")));
+    assert_eq!(makers, expected);
+    assert_eq!(execution.filled_quantity(), d("19.5"));
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use coincidences::{CommitRevealGate, Order, OrderBook, OrderError, ReportKind, Side};
+
+use common::d;
+
+fn bid() -> Order {
+    Order::new("b1", Side::Bid, d("100"), d("1"), 0).with_account("alice")
+}
+
+fn ask() -> Order {
+    Order::new("a1", Side::Ask, d("100"), d("1"), 0).with_account("bob")
+}
+
+/// A gate with commits open until 10 and reveals until 15, holding
+/// commitments for `bid` and `ask`.
+fn gate() -> CommitRevealGate {
+    let mut gate = CommitRevealGate::new(10, 5);
+    gate.commit(CommitRevealGate::commitment(&bid(), b"alice's salt"), 1).unwrap();
+    gate.commit(CommitRevealGate::commitment(&ask(), b"bob's salt"), 2).unwrap();
+    gate
+}
+
+#[test]
+fn commitments_are_taken_once_and_only_in_the_commit_phase() {
+    let mut gate = gate();
+    assert_eq!(
+        gate.commit(CommitRevealGate::commitment(&ask(), b"bob's salt"), 3),
+        Err(OrderError::DuplicateCommitment)
+    );
+    assert_eq!(gate.commit([0; 32], 10), Err(OrderError::CommitPhaseClosed));
+    // The next round opens at 15
+    gate.commit([0; 32], 15).unwrap();
+}
+
+#[test]
+fn a_reveal_must_match_its_commitment() {
+    let mut gate = gate();
+    let raised = Order::new("b1", Side::Bid, d("101"), d("1"), 0).with_account("alice");
+    assert_eq!(gate.reveal(raised, b"alice's salt", 11), Err(OrderError::CommitmentMismatch("b1".to_string())));
+    assert_eq!(gate.reveal(bid(), b"wrong salt", 11), Err(OrderError::CommitmentMismatch("b1".to_string())));
+
+    // Neither the price's scale nor the timestamp is part of the terms
+    let restated = Order::new("b1", Side::Bid, d("100.0"), d("1"), 7).with_account("alice");
+    gate.reveal(restated, b"alice's salt", 11).unwrap();
+    assert_eq!(gate.reveal(bid(), b"alice's salt", 12), Err(OrderError::DuplicateOrderId("b1".to_string())));
+
+    let kinds = gate
+        .drain_reports()
+        .into_iter()
+        .map(|report| report.kind().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ReportKind::Rejected(OrderError::CommitmentMismatch("b1".to_string())),
+            ReportKind::Rejected(OrderError::CommitmentMismatch("b1".to_string())),
+            ReportKind::Rejected(OrderError::DuplicateOrderId("b1".to_string())),
+        ]
+    );
+}
+
+#[test]
+fn revealed_orders_wait_for_the_round_to_close() {
+    let mut gate = gate();
+    gate.reveal(bid(), b"alice's salt", 11).unwrap();
+    gate.reveal(ask(), b"bob's salt", 14).unwrap();
+    assert!(gate.release(14).is_empty());
+
+    let released = gate.release(15);
+    assert_eq!(released.len(), 2);
+    assert!(released.iter().all(|order| order.timestamp() == 15));
+    let mut book = OrderBook::new();
+    let trades: usize = released
+        .into_iter()
+        .map(|order| book.add_order(order).unwrap().trades().len())
+        .sum();
+    assert_eq!(trades, 1);
+    assert!(gate.release(100).is_empty());
+}
+
+#[test]
+fn reveals_wait_for_the_reveal_phase() {
+    let mut gate = gate();
+    assert_eq!(gate.reveal(bid(), b"alice's salt", 2), Err(OrderError::EarlyReveal("b1".to_string())));
+    assert_eq!(gate.reveal(bid(), b"alice's salt", 9), Err(OrderError::EarlyReveal("b1".to_string())));
+    let kinds = gate
+        .drain_reports()
+        .into_iter()
+        .map(|report| report.kind().clone())
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, vec![ReportKind::Rejected(OrderError::EarlyReveal("b1".to_string())); 2]);
+
+    // Refused early reveals don't use up the commitment
+    gate.reveal(bid(), b"alice's salt", 10).unwrap();
+    assert_eq!(gate.release(15).len(), 1);
+}
+
+#[test]
+fn late_reveals_are_refused() {
+    let mut gate = gate();
+    assert_eq!(gate.reveal(ask(), b"bob's salt", 15), Err(OrderError::LateReveal("a1".to_string())));
+    gate.release(15);
+    assert_eq!(gate.reveal(ask(), b"bob's salt", 20), Err(OrderError::LateReveal("a1".to_string())));
+    // More than a round late, the commitment is gone
+    gate.release(30);
+    assert_eq!(gate.reveal(ask(), b"bob's salt", 31), Err(OrderError::CommitmentMismatch("a1".to_string())));
+}
+
+#[test]
+fn release_order_does_not_depend_on_arrival() {
+    let orders: Vec<Order> = (0..8)
+        .map(|index| Order::new(format!("b{index}"), Side::Bid, d("100"), d("1"), 0))
+        .collect();
+    let salt = |order: &Order| format!("salt {}", order.id()).into_bytes();
+    let released = |orders: Vec<Order>| {
+        let mut gate = CommitRevealGate::new(10, 5);
+        for (now, order) in orders.iter().enumerate() {
+            gate.commit(CommitRevealGate::commitment(order, &salt(order)), now as u64).unwrap();
+        }
+        for order in orders {
+            let salt = salt(&order);
+            gate.reveal(order, &salt, 12).unwrap();
+        }
+        gate.release(15).iter().map(|order| order.id().to_string()).collect::<Vec<_>>()
+    };
+
+    let forwards = released(orders.clone());
+    let backwards = released(orders.into_iter().rev().collect());
+    assert_eq!(forwards, backwards);
+}
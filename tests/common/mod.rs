@@ -0,0 +1,6 @@
+use rust_decimal::Decimal;
+
+/// Parses a decimal literal, e.g. `d("10.5")`.
+pub fn d(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
@@ -0,0 +1,223 @@
+mod common;
+
+use coincidences::{DarkOrder, DarkPool, InstrumentSpec, Order, OrderBook, OrderError, ReportKind, Side, TimeInForce};
+
+use common::d;
+
+fn lit_book() -> OrderBook {
+    let spec = InstrumentSpec::new()
+        .with_tick_size(d("1"))
+        .with_lot_size(d("10"))
+        .with_min_quantity(d("20"))
+        .with_max_quantity(d("1000"))
+        .with_min_notional(d("500"));
+    let mut lit = OrderBook::with_spec(spec).unwrap();
+    lit.add_order(Order::new("bid", Side::Bid, d("99"), d("20"), 1)).unwrap();
+    lit.add_order(Order::new("ask", Side::Ask, d("101"), d("20"), 1)).unwrap();
+    lit
+}
+
+#[test]
+fn dark_orders_follow_the_lit_size_rules() {
+    let mut lit = lit_book();
+    let mut pool = DarkPool::new();
+    let dark = |quantity: &str| DarkOrder::new(Order::new("d1", Side::Bid, d("100"), d(quantity), 2));
+
+    assert_eq!(
+        pool.add_order(dark("25"), &mut lit).err(),
+        Some(OrderError::OffLotQuantity {
+            quantity: d("25"),
+            lot_size: d("10"),
+        })
+    );
+    assert_eq!(
+        pool.add_order(dark("10"), &mut lit).err(),
+        Some(OrderError::QuantityBelowMinimum {
+            quantity: d("10"),
+            minimum: d("20"),
+        })
+    );
+    assert_eq!(
+        pool.add_order(dark("2000"), &mut lit).err(),
+        Some(OrderError::QuantityAboveMaximum {
+            quantity: d("2000"),
+            maximum: d("1000"),
+        })
+    );
+    let cheap = DarkOrder::new(Order::new("d1", Side::Bid, d("20.5"), d("20"), 2));
+    assert_eq!(
+        pool.add_order(cheap, &mut lit).err(),
+        Some(OrderError::NotionalBelowMinimum {
+            notional: d("410"),
+            minimum: d("500"),
+        })
+    );
+    let reports = pool.drain_reports();
+    assert_eq!(reports.len(), 4);
+    assert!(reports.iter().all(|report| matches!(report.kind(), ReportKind::Rejected(_))));
+    assert!(pool.orders().is_empty());
+
+    // The limit only caps the midpoint, so it may sit between ticks
+    let execution = pool
+        .add_order(DarkOrder::new(Order::new("d1", Side::Bid, d("100.5"), d("20"), 2)), &mut lit)
+        .unwrap();
+    assert_eq!(execution.resting_quantity(), d("20"));
+}
+
+#[test]
+fn minimum_fill_must_be_whole_lots() {
+    let mut lit = lit_book();
+    let mut pool = DarkPool::new();
+    let order = DarkOrder::new(Order::new("d1", Side::Ask, d("100"), d("50"), 2)).with_min_quantity(d("15"));
+    assert_eq!(
+        pool.add_order(order, &mut lit).err(),
+        Some(OrderError::OffLotQuantity {
+            quantity: d("15"),
+            lot_size: d("10"),
+        })
+    );
+    let order = DarkOrder::new(Order::new("d1", Side::Ask, d("100"), d("50"), 2)).with_min_quantity(d("30"));
+    assert!(pool.add_order(order, &mut lit).is_ok());
+}
+
+/// A lit book quoting 99 / 101, so a midpoint of 100.
+fn quoted_book() -> OrderBook {
+    let mut lit = OrderBook::new();
+    lit.add_order(Order::new("bid", Side::Bid, d("99"), d("1"), 1)).unwrap();
+    lit.add_order(Order::new("ask", Side::Ask, d("101"), d("1"), 1)).unwrap();
+    lit.drain_deltas();
+    lit
+}
+
+fn dark(id: &str, side: Side, price: &str, quantity: &str, account: &str) -> DarkOrder {
+    DarkOrder::new(Order::new(id, side, d(price), d(quantity), 2).with_account(account))
+}
+
+#[test]
+fn orders_cross_at_the_lit_midpoint_unseen() {
+    let mut lit = quoted_book();
+    let mut pool = DarkPool::new();
+    pool.add_order(dark("b1", Side::Bid, "101", "3", "alice"), &mut lit).unwrap();
+    let execution = pool.add_order(dark("s1", Side::Ask, "99", "5", "bob"), &mut lit).unwrap();
+    let trades = execution.trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].price(), trades[0].quantity()), (d("100"), d("3")));
+    assert_eq!((trades[0].maker_order_id(), trades[0].taker_order_id()), ("b1", "s1"));
+    assert_eq!(execution.resting_quantity(), d("2"));
+
+    assert!(lit.drain_deltas().is_empty());
+    assert_eq!(lit.depth(5).asks().len(), 1);
+    assert_eq!(pool.orders().len(), 1);
+}
+
+#[test]
+fn contras_that_cannot_take_the_fill_are_skipped() {
+    let mut lit = quoted_book();
+    let mut pool = DarkPool::new();
+    // Limit below the midpoint
+    pool.add_order(dark("b1", Side::Bid, "99.5", "10", "alice"), &mut lit).unwrap();
+    // Wants at least 3
+    let market = DarkOrder::new(Order::market("b2", Side::Bid, d("3"), 3).with_account("bob"));
+    pool.add_order(market.with_min_quantity(d("3")), &mut lit).unwrap();
+    // Same account as the ask
+    pool.add_order(dark("b3", Side::Bid, "100", "1", "carol"), &mut lit).unwrap();
+
+    let execution = pool.add_order(dark("s1", Side::Ask, "100", "2", "carol"), &mut lit).unwrap();
+    assert!(execution.trades().is_empty());
+
+    let ioc = Order::market("s2", Side::Ask, d("5"), 5)
+        .with_account("dave")
+        .with_time_in_force(TimeInForce::Ioc);
+    let execution = pool.add_order(DarkOrder::new(ioc), &mut lit).unwrap();
+    let makers = execution.trades().iter().map(|trade| trade.maker_order_id()).collect::<Vec<_>>();
+    assert_eq!(makers, vec!["b2", "b3"]);
+    assert_eq!(execution.cancelled_quantity(), d("1"));
+}
+
+#[test]
+fn nothing_trades_without_a_two_sided_lit_market() {
+    let mut lit = OrderBook::new();
+    lit.add_order(Order::new("bid", Side::Bid, d("99"), d("1"), 1)).unwrap();
+    let mut pool = DarkPool::new();
+    pool.add_order(dark("b1", Side::Bid, "101", "1", "alice"), &mut lit).unwrap();
+    let execution = pool.add_order(dark("s1", Side::Ask, "99", "1", "bob"), &mut lit).unwrap();
+    assert!(execution.trades().is_empty());
+
+    lit.add_order(Order::new("ask", Side::Ask, d("101"), d("1"), 3)).unwrap();
+    let execution = pool.cross(&mut lit);
+    assert_eq!(execution.trades().len(), 1);
+    assert_eq!(execution.trades()[0].maker_order_id(), "b1");
+}
+
+#[test]
+fn cross_retries_resting_orders_as_the_midpoint_moves() {
+    let mut lit = quoted_book();
+    let mut pool = DarkPool::new();
+    pool.add_order(dark("b1", Side::Bid, "99.5", "10", "alice"), &mut lit).unwrap();
+    pool.add_order(dark("s1", Side::Ask, "99", "4", "bob"), &mut lit).unwrap();
+    assert!(pool.cross(&mut lit).trades().is_empty());
+
+    // 99 / 100 puts the midpoint at 99.5
+    lit.cancel_order("ask").unwrap();
+    lit.add_order(Order::new("ask2", Side::Ask, d("100"), d("1"), 3)).unwrap();
+    let execution = pool.cross(&mut lit);
+    let trades = execution.trades();
+    assert_eq!(trades.len(), 1);
+    assert_eq!((trades[0].price(), trades[0].quantity()), (d("99.5"), d("4")));
+    assert_eq!(trades[0].maker_order_id(), "b1");
+    assert_eq!(pool.order("b1").map(|order| order.order().quantity()), Some(d("6")));
+}
+
+#[test]
+fn conditionals_are_invited_and_trade_once_firmed_up() {
+    let mut lit = quoted_book();
+    let mut pool = DarkPool::new();
+    pool.add_order(dark("s1", Side::Ask, "100", "2", "bob"), &mut lit).unwrap();
+    let execution = pool
+        .add_order(dark("c1", Side::Bid, "101", "2", "alice").conditional(), &mut lit)
+        .unwrap();
+    assert!(execution.trades().is_empty());
+
+    let invitations = pool.drain_invitations();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].order_id(), "c1");
+    assert_eq!((invitations[0].price(), invitations[0].quantity()), (d("100"), d("2")));
+    // Invited once until it is firmed up or gone
+    pool.cross(&mut lit);
+    assert!(pool.drain_invitations().is_empty());
+
+    let execution = pool.firm_up("c1", &mut lit).unwrap();
+    assert_eq!(execution.trades().len(), 1);
+    assert_eq!(execution.trades()[0].maker_order_id(), "s1");
+    assert!(pool.orders().is_empty());
+
+    let ioc = Order::new("c2", Side::Bid, d("101"), d("1"), 3).with_time_in_force(TimeInForce::Ioc);
+    // A conditional has to rest to be invited
+    assert_eq!(
+        pool.add_order(DarkOrder::new(ioc).conditional(), &mut lit).err(),
+        Some(OrderError::InvalidTimeInForce(TimeInForce::Ioc))
+    );
+    assert!(pool.orders().is_empty());
+}
+
+#[test]
+fn dark_trades_share_the_lit_books_trade_ids() {
+    let mut lit = quoted_book();
+    lit.add_order(Order::new("ask2", Side::Ask, d("102"), d("1"), 1)).unwrap();
+    let mut ids = Vec::new();
+    let lit_trade = |lit: &mut OrderBook, id: &str, side: Side, price: &str| {
+        let order = Order::new(id, side, d(price), d("1"), 3);
+        lit.add_order(order).unwrap().trades().iter().map(|trade| trade.id()).collect::<Vec<_>>()
+    };
+    ids.extend(lit_trade(&mut lit, "t1", Side::Bid, "101"));
+
+    let mut pool = DarkPool::new();
+    pool.add_order(dark("b1", Side::Bid, "101", "1", "alice"), &mut lit).unwrap();
+    let execution = pool.add_order(dark("s1", Side::Ask, "99", "1", "bob"), &mut lit).unwrap();
+    assert_eq!(execution.trades()[0].price(), d("100.5"));
+    ids.extend(execution.trades().iter().map(|trade| trade.id()));
+
+    ids.extend(lit_trade(&mut lit, "t2", Side::Ask, "99"));
+    assert_eq!(ids, vec![1, 2, 3]);
+    assert_eq!(lit.snapshot().last_trade_id(), 3);
+}
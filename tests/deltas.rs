@@ -0,0 +1,96 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use coincidences::{BookDelta, DeltaAction, Order, OrderBook, Side, TimeInForce};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn summary(deltas: &[BookDelta]) -> Vec<(u64, Side, DeltaAction, Decimal, Decimal)> {
+    deltas
+        .iter()
+        .map(|delta| (delta.sequence(), delta.side(), delta.action(), delta.price(), delta.quantity()))
+        .collect()
+}
+
+#[test]
+fn each_changed_level_gets_one_sequenced_delta() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("11"), d("2"), 1)).unwrap();
+    assert_eq!(
+        summary(&book.drain_deltas()),
+        vec![
+            (1, Side::Ask, DeltaAction::Add, d("10"), d("2")),
+            (2, Side::Ask, DeltaAction::Update, d("10"), d("4")),
+            (3, Side::Ask, DeltaAction::Add, d("11"), d("2")),
+        ]
+    );
+    let snapshot = book.snapshot();
+    assert_eq!(snapshot.sequence(), 3);
+    assert_eq!(snapshot.engine_sequence(), 3);
+
+    // One call sweeping two levels and resting the rest
+    book.add_order(Order::new("b1", Side::Bid, d("11"), d("7"), 2)).unwrap();
+    assert_eq!(
+        summary(&book.drain_deltas()),
+        vec![
+            (4, Side::Bid, DeltaAction::Add, d("11"), d("1")),
+            (5, Side::Ask, DeltaAction::Delete, d("10"), d("0")),
+            (6, Side::Ask, DeltaAction::Delete, d("11"), d("0")),
+        ]
+    );
+    book.cancel_order("b1").unwrap();
+    assert_eq!(summary(&book.drain_deltas()), vec![(7, Side::Bid, DeltaAction::Delete, d("11"), d("0"))]);
+    assert_eq!(OrderBook::from_snapshot(&book.snapshot()).unwrap().sequence(), 7);
+}
+
+#[test]
+fn calls_that_leave_levels_as_they_were_publish_nothing() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.drain_deltas();
+    let sequence = book.sequence();
+
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("1"), 2).with_time_in_force(TimeInForce::PostOnly))
+        .unwrap_err();
+    book.cancel_order("nope").unwrap_err();
+    assert!(book.drain_deltas().is_empty());
+    assert_eq!(book.sequence(), sequence);
+}
+
+#[test]
+fn snapshot_plus_later_deltas_rebuilds_the_depth() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("9"), d("2"), 1)).unwrap();
+    let snapshot = book.snapshot();
+    let mut levels: BTreeMap<(bool, Decimal), Decimal> = BTreeMap::new();
+    for order in snapshot.bids().iter().chain(snapshot.asks()) {
+        let price = order.price().unwrap();
+        *levels.entry((order.side() == Side::Ask, price)).or_default() += order.visible_quantity();
+    }
+
+    book.add_order(Order::new("a2", Side::Ask, d("11"), d("3"), 2)).unwrap();
+    book.add_order(Order::new("b2", Side::Bid, d("10"), d("3"), 3)).unwrap();
+    book.amend_order("a2", d("11"), d("1")).unwrap();
+    for delta in book.drain_deltas().iter().filter(|delta| delta.sequence() > snapshot.sequence()) {
+        let key = (delta.side() == Side::Ask, delta.price());
+        match delta.action() {
+            DeltaAction::Delete => levels.remove(&key),
+            DeltaAction::Add | DeltaAction::Update => levels.insert(key, delta.quantity()),
+        };
+    }
+
+    let depth = book.depth(10);
+    let mut expected = BTreeMap::new();
+    for level in depth.bids() {
+        expected.insert((false, level.price()), level.quantity());
+    }
+    for level in depth.asks() {
+        expected.insert((true, level.price()), level.quantity());
+    }
+    assert_eq!(levels, expected);
+}
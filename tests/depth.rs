@@ -0,0 +1,72 @@
+mod common;
+
+use coincidences::{Depth, Order, OrderBook, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn book() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("5"), 1).with_display_quantity(d("2")))
+        .unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("11"), d("4"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("9"), d("4"), 1)).unwrap();
+    book.add_order(Order::new("b2", Side::Bid, d("8"), d("4"), 1)).unwrap();
+    book
+}
+
+fn levels(depth: &Depth, side: Side) -> Vec<(Decimal, Decimal, usize)> {
+    let levels = match side {
+        Side::Bid => depth.bids(),
+        Side::Ask => depth.asks(),
+    };
+    levels
+        .iter()
+        .map(|level| (level.price(), level.quantity(), level.order_count()))
+        .collect()
+}
+
+#[test]
+fn empty_book_has_no_top() {
+    let book = OrderBook::new();
+    assert!(book.best_bid().is_none());
+    assert!(book.best_ask().is_none());
+    assert!(book.spread().is_none());
+    assert!(book.mid().is_none());
+}
+
+#[test]
+fn top_of_book_shows_visible_quantity_only() {
+    let book = book();
+    let best_ask = book.best_ask().unwrap();
+    assert_eq!((best_ask.price(), best_ask.quantity(), best_ask.order_count()), (d("10"), d("3"), 2));
+    assert_eq!(book.best_bid().map(|level| level.price()), Some(d("9")));
+    assert_eq!(book.spread(), Some(d("1")));
+    assert_eq!(book.mid(), Some(d("9.5")));
+}
+
+#[test]
+fn depth_lists_levels_best_first() {
+    let book = book();
+    let depth = book.depth(5);
+    assert_eq!(levels(&depth, Side::Ask), vec![(d("10"), d("3"), 2), (d("11"), d("4"), 1)]);
+    assert_eq!(levels(&depth, Side::Bid), vec![(d("9"), d("4"), 1), (d("8"), d("4"), 1)]);
+    assert_eq!(book.depth(1).bids().len(), 1);
+
+    let cumulative = book.cumulative_depth(5);
+    assert_eq!(levels(&cumulative, Side::Ask), vec![(d("10"), d("3"), 2), (d("11"), d("7"), 3)]);
+    assert_eq!(levels(&cumulative, Side::Bid), vec![(d("9"), d("4"), 1), (d("8"), d("8"), 2)]);
+}
+
+#[test]
+fn depth_follows_fills_refreshes_and_amends() {
+    let mut book = book();
+    book.add_order(Order::new("t1", Side::Bid, d("10"), d("2"), 2)).unwrap();
+    assert_eq!(book.best_ask().map(|level| level.quantity()), Some(d("1")));
+    // The iceberg's slice is used up and refreshed from its reserve
+    book.add_order(Order::new("t2", Side::Bid, d("10"), d("1"), 3)).unwrap();
+    assert_eq!(book.best_ask().map(|level| level.quantity()), Some(d("2")));
+    book.amend_order("a2", d("10"), d("1")).unwrap();
+    assert_eq!(book.best_ask().map(|level| level.quantity()), Some(d("1")));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use coincidences::{InstrumentSpec, MatchingEngine, Order, OrderBook, OrderError, Side, TradingStatus};
+
+use common::d;
+
+fn engine() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine.add_instrument("ETH-USD", InstrumentSpec::new()).unwrap();
+    engine
+        .add_instrument("BTC-USD", InstrumentSpec::new().with_tick_size(d("1")))
+        .unwrap();
+    engine
+}
+
+#[test]
+fn symbols_are_listed_once() {
+    let mut engine = engine();
+    assert_eq!(
+        engine.add_instrument("ETH-USD", InstrumentSpec::new()),
+        Err(OrderError::DuplicateSymbol("ETH-USD".to_string()))
+    );
+    assert_eq!(engine.symbols().collect::<Vec<_>>(), vec!["BTC-USD", "ETH-USD"]);
+    assert_eq!(
+        engine.add_order("XRP-USD", Order::new("b1", Side::Bid, d("1"), d("1"), 1)),
+        Err(OrderError::UnknownSymbol("XRP-USD".to_string()))
+    );
+
+    engine.add_order("ETH-USD", Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    let book = engine.remove_instrument("ETH-USD").unwrap();
+    assert!(book.order("a1").is_some());
+    assert_eq!(engine.symbols().collect::<Vec<_>>(), vec!["BTC-USD"]);
+    engine.add_book("ETH-USD", OrderBook::from_snapshot(&book.snapshot()).unwrap()).unwrap();
+    assert_eq!(engine.find_order("a1").map(|(symbol, _)| symbol), Some("ETH-USD"));
+}
+
+#[test]
+fn orders_go_to_their_own_book() {
+    let mut engine = engine();
+    engine.add_order("ETH-USD", Order::new("a1", Side::Ask, d("10.5"), d("5"), 1)).unwrap();
+    assert_eq!(
+        engine.add_order("BTC-USD", Order::new("a2", Side::Ask, d("10.5"), d("5"), 1)),
+        Err(OrderError::OffTickPrice {
+            price: d("10.5"),
+            tick_size: d("1"),
+        })
+    );
+    // Same price on the other book doesn't cross
+    let execution = engine.add_order("BTC-USD", Order::new("b1", Side::Bid, d("11"), d("1"), 2)).unwrap();
+    assert!(execution.trades().is_empty());
+    let execution = engine.add_order("ETH-USD", Order::new("b2", Side::Bid, d("11"), d("2"), 3)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("2"));
+
+    assert_eq!(engine.last_prices().collect::<Vec<_>>(), vec![("ETH-USD", d("10.5"))]);
+    let tops = engine
+        .top_of_book()
+        .map(|(symbol, bid, ask)| (symbol, bid.map(|level| level.price()), ask.map(|level| level.price())))
+        .collect::<Vec<_>>();
+    assert_eq!(tops, vec![("BTC-USD", Some(d("11")), None), ("ETH-USD", None, Some(d("10.5")))]);
+
+    let reports = engine.drain_reports();
+    let ids = |listed: &str| {
+        let mut ids = reports
+            .iter()
+            .filter(|(symbol, _)| symbol == listed)
+            .map(|(_, report)| report.order_id())
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids.dedup();
+        ids
+    };
+    assert_eq!(ids("BTC-USD"), vec!["a2", "b1"]);
+    assert_eq!(ids("ETH-USD"), vec!["a1", "b2"]);
+}
+
+#[test]
+fn trading_status_gates_requests() {
+    let mut engine = engine();
+    engine.add_order("ETH-USD", Order::new("a1", Side::Ask, d("10"), d("5"), 1)).unwrap();
+
+    engine.set_trading_status("ETH-USD", TradingStatus::CancelOnly).unwrap();
+    assert_eq!(
+        engine.add_order("ETH-USD", Order::new("b1", Side::Bid, d("10"), d("1"), 2)),
+        Err(OrderError::SymbolNotTrading {
+            symbol: "ETH-USD".to_string(),
+            status: TradingStatus::CancelOnly,
+        })
+    );
+    // Other books keep trading
+    engine.add_order("BTC-USD", Order::new("b1", Side::Bid, d("10"), d("1"), 2)).unwrap();
+
+    engine.set_trading_status("ETH-USD", TradingStatus::Halted).unwrap();
+    assert_eq!(
+        engine.cancel_order("ETH-USD", "a1"),
+        Err(OrderError::SymbolNotTrading {
+            symbol: "ETH-USD".to_string(),
+            status: TradingStatus::Halted,
+        })
+    );
+    engine.set_trading_status("ETH-USD", TradingStatus::CancelOnly).unwrap();
+    assert_eq!(engine.cancel_order("ETH-USD", "a1").map(|order| order.quantity()), Ok(d("5")));
+    assert_eq!(engine.trading_status("ETH-USD"), Some(TradingStatus::CancelOnly));
+}
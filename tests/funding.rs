@@ -0,0 +1,201 @@
+mod common;
+
+use coincidences::{
+    InstrumentSpec, MatchingEngine, Order, OrderError, ReportKind, SelfTradePrevention, Side, TimeInForce,
+    TradingStatus,
+};
+
+use common::d;
+
+fn funded_engine() -> MatchingEngine {
+    let mut engine = MatchingEngine::new();
+    engine
+        .add_instrument("ETH-USD", InstrumentSpec::new().with_assets("ETH", "USD"))
+        .unwrap();
+    engine
+}
+
+#[test]
+fn rejected_post_only_expires_nothing() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("100")).unwrap();
+    engine.ledger_mut().deposit("bob", "USD", d("100")).unwrap();
+    engine.ledger_mut().deposit("carol", "ETH", d("1")).unwrap();
+    let gtt = Order::new("g", Side::Bid, d("10"), d("1"), 1)
+        .with_account("alice")
+        .with_time_in_force(TimeInForce::Gtt(5));
+    engine.add_order("ETH-USD", gtt).unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("b", Side::Bid, d("11"), d("1"), 2).with_account("bob"))
+        .unwrap();
+    engine.drain_deltas();
+
+    let post_only = Order::new("p", Side::Ask, d("11"), d("1"), 6)
+        .with_account("carol")
+        .with_time_in_force(TimeInForce::PostOnly);
+    assert_eq!(engine.add_order("ETH-USD", post_only), Err(OrderError::WouldTakeLiquidity));
+    assert!(engine.find_order("g").is_some());
+    assert!(engine.drain_deltas().is_empty());
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("10"));
+    assert_eq!(engine.ledger().balance("carol", "ETH").available(), d("1"));
+
+    let expired = engine.expire_orders(6);
+    assert_eq!(expired.len(), 1);
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("100"));
+}
+
+#[test]
+fn post_only_ignores_touch_expiring_on_arrival() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("100")).unwrap();
+    engine.ledger_mut().deposit("carol", "ETH", d("1")).unwrap();
+    let gtt = Order::new("g", Side::Bid, d("10"), d("1"), 1)
+        .with_account("alice")
+        .with_time_in_force(TimeInForce::Gtt(5));
+    engine.add_order("ETH-USD", gtt).unwrap();
+
+    let post_only = Order::new("p", Side::Ask, d("10"), d("1"), 5)
+        .with_account("carol")
+        .with_time_in_force(TimeInForce::PostOnly);
+    let execution = engine.add_order("ETH-USD", post_only).unwrap();
+    assert_eq!(execution.expired_orders().len(), 1);
+    assert_eq!(execution.resting_quantity(), d("1"));
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+    assert_eq!(engine.ledger().balance("carol", "ETH").reserved(), d("1"));
+}
+
+#[test]
+fn orders_need_an_account_and_the_funds() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("1000")).unwrap();
+    assert_eq!(
+        engine.add_order("ETH-USD", Order::new("b1", Side::Bid, d("10"), d("5"), 1)),
+        Err(OrderError::MissingAccount("b1".to_string()))
+    );
+    assert_eq!(
+        engine.add_order("ETH-USD", Order::new("b1", Side::Bid, d("300"), d("5"), 1).with_account("alice")),
+        Err(OrderError::InsufficientFunds {
+            account: "alice".to_string(),
+            asset: "USD".to_string(),
+            required: d("1500"),
+            available: d("1000"),
+        })
+    );
+    assert_eq!(
+        engine.add_order("ETH-USD", Order::market("b1", Side::Bid, d("5"), 1).with_account("alice")),
+        Err(OrderError::UnpricedBid("b1".to_string()))
+    );
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+}
+
+#[test]
+fn fills_pay_out_of_the_reservation() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("1000")).unwrap();
+    engine.ledger_mut().deposit("bob", "ETH", d("10")).unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("b1", Side::Bid, d("100"), d("5"), 1).with_account("alice"))
+        .unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("500"));
+
+    // The resting bid pays its own price
+    engine
+        .add_order("ETH-USD", Order::new("a1", Side::Ask, d("90"), d("3"), 2).with_account("bob"))
+        .unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("200"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("500"));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("3"));
+    assert_eq!(engine.ledger().balance("bob", "USD").available(), d("300"));
+    assert_eq!(engine.ledger().balance("bob", "ETH").available(), d("7"));
+    assert_eq!(engine.ledger().balance("bob", "ETH").reserved(), d("0"));
+
+    // A bid filled below its limit gets the difference back
+    engine
+        .add_order("ETH-USD", Order::new("a2", Side::Ask, d("110"), d("2"), 3).with_account("bob"))
+        .unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("b2", Side::Bid, d("120"), d("2"), 4).with_account("alice"))
+        .unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("200"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("280"));
+
+    engine.cancel_order("ETH-USD", "b1").unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("0"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("480"));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("5"));
+}
+
+#[test]
+fn amends_reserve_or_release_the_difference() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("480")).unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("b1", Side::Bid, d("100"), d("4"), 1).with_account("alice"))
+        .unwrap();
+    assert_eq!(
+        engine.amend_order("ETH-USD", "b1", d("100"), d("5")),
+        Err(OrderError::InsufficientFunds {
+            account: "alice".to_string(),
+            asset: "USD".to_string(),
+            required: d("100"),
+            available: d("80"),
+        })
+    );
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("400"));
+
+    engine.amend_order("ETH-USD", "b1", d("50"), d("4")).unwrap();
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("200"));
+    assert!(engine.ledger_mut().withdraw("alice", "USD", d("281")).is_err());
+    engine.ledger_mut().withdraw("alice", "USD", d("280")).unwrap();
+}
+
+#[test]
+fn engine_rejections_are_reported_and_sequenced() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("100")).unwrap();
+    engine.add_order("ETH-USD", Order::new("b1", Side::Bid, d("300"), d("5"), 1)).unwrap_err();
+    engine
+        .add_order("ETH-USD", Order::new("b2", Side::Bid, d("300"), d("5"), 1).with_account("alice"))
+        .unwrap_err();
+    engine
+        .add_order("ETH-USD", Order::new("b3", Side::Bid, d("10"), d("5"), 1).with_account("alice"))
+        .unwrap();
+    engine.amend_order("ETH-USD", "b3", d("30"), d("5")).unwrap_err();
+    engine.set_trading_status("ETH-USD", TradingStatus::Halted).unwrap();
+    let halted = engine.add_order("ETH-USD", Order::new("b4", Side::Bid, d("10"), d("1"), 2).with_account("alice"));
+    assert!(matches!(halted, Err(OrderError::SymbolNotTrading { .. })));
+    assert_eq!(engine.book("ETH-USD").unwrap().engine_sequence(), 5);
+
+    let reports: Vec<(String, bool)> = engine
+        .drain_reports()
+        .into_iter()
+        .map(|(_, report)| (report.order_id().to_string(), matches!(report.kind(), ReportKind::Rejected(_))))
+        .collect();
+    let expected = [("b1", true), ("b2", true), ("b3", false), ("b3", true), ("b4", true)];
+    assert_eq!(reports, expected.map(|(id, rejected)| (id.to_string(), rejected)));
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("50"));
+}
+
+#[test]
+fn self_trade_reductions_release_the_makers_surplus() {
+    let mut engine = funded_engine();
+    engine.ledger_mut().deposit("alice", "USD", d("1000")).unwrap();
+    engine.ledger_mut().deposit("alice", "ETH", d("2")).unwrap();
+    engine
+        .book_mut("ETH-USD")
+        .unwrap()
+        .set_self_trade_prevention(SelfTradePrevention::DecrementAndCancel);
+    engine
+        .add_order("ETH-USD", Order::new("b1", Side::Bid, d("100"), d("5"), 1).with_account("alice"))
+        .unwrap();
+
+    let execution = engine
+        .add_order("ETH-USD", Order::new("a1", Side::Ask, d("100"), d("2"), 2).with_account("alice"))
+        .unwrap();
+    assert_eq!(execution.reduced_order_ids(), &["b1".to_string()]);
+    assert_eq!(engine.find_order("b1").map(|(_, order)| order.quantity()), Some(d("3")));
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("300"));
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("700"));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("2"));
+}
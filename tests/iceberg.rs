@@ -0,0 +1,39 @@
+mod common;
+
+use coincidences::{Execution, Order, OrderBook, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn fills(execution: &Execution) -> Vec<(&str, Decimal)> {
+    execution
+        .trades()
+        .iter()
+        .map(|trade| (trade.maker_order_id(), trade.quantity()))
+        .collect()
+}
+
+#[test]
+fn iceberg_shows_only_its_display_quantity() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("ice", Side::Ask, d("10"), d("5"), 1).with_display_quantity(d("2")))
+        .unwrap();
+    assert_eq!(book.order("ice").map(Order::visible_quantity), Some(d("2")));
+    assert_eq!(book.best_ask().map(|level| level.quantity()), Some(d("2")));
+}
+
+#[test]
+fn refreshed_slice_goes_to_the_back_of_the_queue() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("ice", Side::Ask, d("10"), d("5"), 1).with_display_quantity(d("2")))
+        .unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("3"), 1)).unwrap();
+
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("4"), 2)).unwrap();
+    assert_eq!(fills(&execution), vec![("ice", d("2")), ("a1", d("2"))]);
+
+    let execution = book.add_order(Order::new("b2", Side::Bid, d("10"), d("10"), 3)).unwrap();
+    assert_eq!(fills(&execution), vec![("a1", d("1")), ("ice", d("2")), ("ice", d("1"))]);
+    assert!(book.order("ice").is_none());
+    assert_eq!(execution.resting_quantity(), d("6"));
+}
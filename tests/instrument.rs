@@ -0,0 +1,124 @@
+mod common;
+
+use coincidences::{InstrumentSpec, MatchingEngine, Order, OrderBook, OrderError, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+#[test]
+fn non_positive_tick_size_is_refused() {
+    assert_eq!(
+        OrderBook::with_tick_size(Decimal::ZERO).err(),
+        Some(OrderError::InvalidTickSize(Decimal::ZERO))
+    );
+    assert_eq!(
+        OrderBook::with_tick_size(d("-0.5")).err(),
+        Some(OrderError::InvalidTickSize(d("-0.5")))
+    );
+}
+
+#[test]
+fn non_positive_lot_size_is_refused() {
+    let spec = InstrumentSpec::new().with_lot_size(Decimal::ZERO);
+    assert_eq!(spec.validate(), Err(OrderError::InvalidLotSize(Decimal::ZERO)));
+
+    let mut engine = MatchingEngine::new();
+    assert_eq!(
+        engine.add_instrument("BTC-USD", InstrumentSpec::new().with_lot_size(d("-1"))),
+        Err(OrderError::InvalidLotSize(d("-1")))
+    );
+    assert!(engine.book("BTC-USD").is_none());
+}
+
+#[test]
+fn unrepresentable_notional_is_refused() {
+    let huge = d("10000000000000000000");
+    let overflow = OrderError::NotionalOverflow {
+        price: huge,
+        quantity: huge,
+    };
+    let mut book = OrderBook::new();
+    assert_eq!(
+        book.add_order(Order::new("b1", Side::Bid, huge, huge, 1)),
+        Err(overflow.clone())
+    );
+    book.add_order(Order::new("b2", Side::Bid, d("1"), huge, 2)).unwrap();
+    assert_eq!(book.amend_order("b2", huge, huge), Err(overflow.clone()));
+
+    let mut engine = MatchingEngine::new();
+    engine
+        .add_instrument("BTC-USD", InstrumentSpec::new().with_assets("BTC", "USD"))
+        .unwrap();
+    engine.ledger_mut().deposit("alice", "USD", d("100000000000000000000")).unwrap();
+    let bid = Order::new("b1", Side::Bid, huge, huge, 1).with_account("alice");
+    assert_eq!(engine.add_order("BTC-USD", bid), Err(overflow.clone()));
+    let bid = Order::new("b2", Side::Bid, d("1"), d("10"), 2).with_account("alice");
+    engine.add_order("BTC-USD", bid).unwrap();
+    assert_eq!(engine.amend_order("BTC-USD", "b2", huge, huge), Err(overflow));
+    assert_eq!(engine.ledger().balance("alice", "USD").reserved(), d("10"));
+}
+
+fn bid(price: &str, quantity: &str) -> Order {
+    Order::new("b1", Side::Bid, d(price), d(quantity), 1)
+}
+
+#[test]
+fn orders_must_meet_every_instrument_rule() {
+    let spec = InstrumentSpec::new()
+        .with_tick_size(d("0.05"))
+        .with_lot_size(d("10"))
+        .with_min_quantity(d("20"))
+        .with_max_quantity(d("1000"))
+        .with_min_notional(d("100"))
+        .with_price_precision(2);
+    let mut book = OrderBook::with_spec(spec.clone()).unwrap();
+
+    assert_eq!(
+        book.add_order(bid("10.05", "15")).err(),
+        Some(OrderError::OffLotQuantity {
+            quantity: d("15"),
+            lot_size: d("10"),
+        })
+    );
+    assert_eq!(
+        book.add_order(bid("10.05", "10")).err(),
+        Some(OrderError::QuantityBelowMinimum {
+            quantity: d("10"),
+            minimum: d("20"),
+        })
+    );
+    assert_eq!(
+        book.add_order(bid("10.05", "2000")).err(),
+        Some(OrderError::QuantityAboveMaximum {
+            quantity: d("2000"),
+            maximum: d("1000"),
+        })
+    );
+    assert_eq!(
+        book.add_order(bid("1.05", "20")).err(),
+        Some(OrderError::NotionalBelowMinimum {
+            notional: d("21"),
+            minimum: d("100"),
+        })
+    );
+    assert_eq!(
+        book.add_order(bid("10.005", "20")).err(),
+        Some(OrderError::ExcessPricePrecision {
+            price: d("10.005"),
+            precision: 2,
+        })
+    );
+    assert_eq!(
+        book.add_order(bid("10.03", "20")).err(),
+        Some(OrderError::OffTickPrice {
+            price: d("10.03"),
+            tick_size: d("0.05"),
+        })
+    );
+
+    // Trailing zeros don't count against the precision
+    book.add_order(bid("10.050", "20")).unwrap();
+    // Market orders have no notional to check
+    book.add_order(Order::market("m1", Side::Ask, d("20"), 2)).unwrap();
+    assert_eq!(OrderBook::from_snapshot(&book.snapshot()).unwrap().spec(), &spec);
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use coincidences::{Order, OrderBook, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+#[test]
+fn market_order_sweeps_levels_and_cancels_the_rest() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("12"), d("2"), 1)).unwrap();
+
+    let execution = book.add_order(Order::market("m1", Side::Bid, d("5"), 2)).unwrap();
+    let prices: Vec<Decimal> = execution.trades().iter().map(|trade| trade.price()).collect();
+    assert_eq!(prices, vec![d("10"), d("12")]);
+    assert_eq!(execution.filled_quantity(), d("4"));
+    assert_eq!(execution.cancelled_quantity(), d("1"));
+    assert_eq!(execution.resting_quantity(), d("0"));
+    assert!(book.order("m1").is_none());
+    assert!(book.best_ask().is_none());
+}
+
+#[test]
+fn market_order_against_an_empty_side_never_rests() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("2"), 1)).unwrap();
+    let execution = book.add_order(Order::market("m1", Side::Bid, d("5"), 2)).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.cancelled_quantity(), d("5"));
+    assert!(book.order("m1").is_none());
+    assert_eq!(book.best_bid().map(|level| level.quantity()), Some(d("2")));
+}
@@ -0,0 +1,102 @@
+mod common;
+
+use coincidences::{Execution, InstrumentSpec, MatchingPolicy, Order, OrderBook, OrderError, ProRata, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn fills(execution: &Execution) -> Vec<(&str, Decimal)> {
+    execution
+        .trades()
+        .iter()
+        .map(|trade| (trade.maker_order_id(), trade.quantity()))
+        .collect()
+}
+
+#[test]
+fn fifo_is_the_default() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("10"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("30"), 2)).unwrap();
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("10"), 3)).unwrap();
+    assert_eq!(fills(&execution), vec![("a1", d("10"))]);
+}
+
+#[test]
+fn pro_rata_shares_by_size_in_whole_lots() {
+    let mut book = OrderBook::new();
+    book.set_matching_policy(ProRata::new(d("1"))).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("10"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("30"), 2)).unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("11"), d("30"), 3)).unwrap();
+
+    // 2.5 and 7.5 round down to 2 and 7; the leftover lot goes to the front
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("10"), 4)).unwrap();
+    assert_eq!(fills(&execution), vec![("a1", d("3")), ("a2", d("7"))]);
+}
+
+#[test]
+fn top_of_book_and_lead_market_makers_fill_first() {
+    let mut book = OrderBook::new();
+    let policy = ProRata::new(d("1"))
+        .with_top_of_book_priority()
+        .with_lead_market_makers(["L"], d("0.5"));
+    book.set_matching_policy(policy).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("lmm", Side::Ask, d("10"), d("20"), 2).with_account("L")).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("20"), 3)).unwrap();
+
+    // a1 fills whole at the front, L takes half of the other 10, then 5 is shared
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("12"), 4)).unwrap();
+    assert_eq!(fills(&execution), vec![("a1", d("2")), ("lmm", d("8")), ("a2", d("2"))]);
+
+    let execution = book.add_order(Order::new("b2", Side::Bid, d("10"), d("100"), 5)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("30"));
+    assert_eq!(execution.resting_quantity(), d("70"));
+}
+
+#[test]
+fn pro_rata_lots_must_be_whole_lots_of_the_instrument() {
+    let mut book = OrderBook::with_spec(InstrumentSpec::new().with_lot_size(d("2"))).unwrap();
+    for lot_size in ["0", "1", "3"] {
+        assert_eq!(
+            book.set_matching_policy(ProRata::new(d(lot_size))),
+            Err(OrderError::PolicyLotMismatch { policy_lot_size: d(lot_size), lot_size: d("2") })
+        );
+    }
+    book.set_matching_policy(ProRata::new(d("4"))).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("10"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("30"), 2)).unwrap();
+
+    // Shares of 2.5 and 7.5 round down to 0 and 4; the rest goes in queue order
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("10"), 3)).unwrap();
+    assert_eq!(fills(&execution), vec![("a1", d("6")), ("a2", d("4"))]);
+}
+
+/// Fills the back of the queue first.
+struct Lifo;
+
+impl MatchingPolicy for Lifo {
+    fn allocate(&self, orders: &[&Order], quantity: Decimal) -> Vec<(usize, Decimal)> {
+        let mut left = quantity;
+        let mut allocations = Vec::new();
+        for (index, order) in orders.iter().enumerate().rev() {
+            let fill = left.min(order.visible_quantity());
+            if fill > Decimal::ZERO {
+                allocations.push((index, fill));
+                left -= fill;
+            }
+        }
+        allocations
+    }
+}
+
+#[test]
+fn custom_policies_plug_in() {
+    let mut book = OrderBook::new();
+    book.set_matching_policy(Lifo).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("2"), 2)).unwrap();
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("3"), 3)).unwrap();
+    assert_eq!(fills(&execution), vec![("a2", d("2")), ("a1", d("1"))]);
+}
@@ -0,0 +1,73 @@
+mod common;
+
+use coincidences::{Order, OrderBook, OrderError, Side, Trade};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn fills(trades: &[Trade]) -> Vec<(&str, Decimal, Decimal)> {
+    trades
+        .iter()
+        .map(|trade| (trade.maker_order_id(), trade.price(), trade.quantity()))
+        .collect()
+}
+
+#[test]
+fn crossing_order_trades_best_price_first_at_maker_prices() {
+    let mut book = OrderBook::new();
+    assert!(book.add_order(Order::new("a1", Side::Ask, d("11"), d("5"), 1)).unwrap().trades().is_empty());
+    assert!(book.add_order(Order::new("a2", Side::Ask, d("10"), d("5"), 2)).unwrap().trades().is_empty());
+
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("11"), d("7"), 3)).unwrap();
+    assert_eq!(fills(execution.trades()), vec![("a2", d("10"), d("5")), ("a1", d("11"), d("2"))]);
+    assert_eq!(execution.filled_quantity(), d("7"));
+    assert_eq!(execution.resting_quantity(), d("0"));
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("3")));
+    assert!(book.order("a2").is_none());
+}
+
+#[test]
+fn equal_prices_fill_in_time_priority() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("1"), 2)).unwrap();
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("3"), 3)).unwrap();
+    assert_eq!(fills(execution.trades()), vec![("a1", d("10"), d("1")), ("a2", d("10"), d("1"))]);
+    assert_eq!(execution.resting_quantity(), d("1"));
+    assert_eq!(book.order("b1").map(Order::quantity), Some(d("1")));
+}
+
+#[test]
+fn invalid_orders_are_rejected_with_typed_errors() {
+    let mut book = OrderBook::with_tick_size(d("0.5")).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("5"), 1)).unwrap();
+
+    assert_eq!(
+        book.add_order(Order::new("a1", Side::Ask, d("10"), d("5"), 2)),
+        Err(OrderError::DuplicateOrderId("a1".to_string()))
+    );
+    assert_eq!(
+        book.add_order(Order::new("x", Side::Ask, d("10"), d("0"), 2)),
+        Err(OrderError::InvalidQuantity(d("0")))
+    );
+    assert_eq!(
+        book.add_order(Order::new("x", Side::Ask, d("-1"), d("1"), 2)),
+        Err(OrderError::InvalidPrice(d("-1")))
+    );
+    assert_eq!(
+        book.add_order(Order::new("x", Side::Ask, d("10.2"), d("1"), 2)),
+        Err(OrderError::OffTickPrice {
+            price: d("10.2"),
+            tick_size: d("0.5"),
+        })
+    );
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("5")));
+}
+
+#[test]
+fn ids_of_finished_orders_can_be_reused() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("5"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("5"), 2)).unwrap();
+    assert!(book.add_order(Order::new("a1", Side::Bid, d("9"), d("1"), 3)).is_ok());
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use coincidences::{ExecutionReport, InstrumentSpec, Order, OrderBook, OrderError, ReportKind, Side, TimeInForce};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn rejection(report: &ExecutionReport) -> Option<&OrderError> {
+    match report.kind() {
+        ReportKind::Rejected(error) => Some(error),
+        _ => None,
+    }
+}
+
+#[test]
+fn refused_amend_of_live_order_is_reported_with_its_leaves() {
+    let mut book = OrderBook::with_spec(InstrumentSpec::new().with_tick_size(d("1"))).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("5"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("2"), 2)).unwrap();
+    let bid = Order::new("b2", Side::Bid, d("8"), d("4"), 3).with_time_in_force(TimeInForce::PostOnly);
+    book.add_order(bid).unwrap();
+    book.drain_reports();
+
+    let error = book.amend_order("a1", d("10.5"), d("3")).unwrap_err();
+    let reports = book.drain_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].order_id(), "a1");
+    assert_eq!(rejection(&reports[0]), Some(&error));
+    assert_eq!(reports[0].price(), Some(d("10")));
+    assert_eq!(reports[0].cumulative_quantity(), d("2"));
+    assert_eq!(reports[0].leaves_quantity(), d("3"));
+
+    let error = book.amend_order("b2", d("10"), d("4")).unwrap_err();
+    assert_eq!(error, OrderError::WouldTakeLiquidity);
+    let reports = book.drain_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(rejection(&reports[0]), Some(&error));
+    assert_eq!(reports[0].leaves_quantity(), d("4"));
+    assert_eq!(book.order("b2").map(Order::price), Some(Some(d("8"))));
+}
+
+#[test]
+fn amend_of_unknown_order_is_reported() {
+    let mut book = OrderBook::new();
+    let error = book.amend_order("missing", d("10"), d("1")).unwrap_err();
+    assert_eq!(error, OrderError::OrderNotFound("missing".to_string()));
+    let reports = book.drain_reports();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].order_id(), "missing");
+    assert_eq!(reports[0].side(), None);
+    assert_eq!(rejection(&reports[0]), Some(&error));
+}
+
+/// A report as order id, kind without its data, cumulative and leaves.
+type Step = (String, &'static str, Decimal, Decimal);
+
+fn lifecycle(book: &mut OrderBook) -> Vec<Step> {
+    book.drain_reports()
+        .into_iter()
+        .map(|report| {
+            let kind = match report.kind() {
+                ReportKind::Accepted => "Accepted",
+                ReportKind::PartiallyFilled { .. } => "PartiallyFilled",
+                ReportKind::Filled { .. } => "Filled",
+                ReportKind::Replaced => "Replaced",
+                ReportKind::Cancelled => "Cancelled",
+                ReportKind::Expired => "Expired",
+                ReportKind::Rejected(_) => "Rejected",
+            };
+            (report.order_id().to_string(), kind, report.cumulative_quantity(), report.leaves_quantity())
+        })
+        .collect()
+}
+
+fn step(id: &str, kind: &'static str, cumulative: &str, leaves: &str) -> Step {
+    (id.to_string(), kind, d(cumulative), d(leaves))
+}
+
+#[test]
+fn entry_and_rejection_are_reported() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("bad", Side::Ask, d("11"), d("0"), 1)).unwrap_err();
+    assert_eq!(
+        lifecycle(&mut book),
+        vec![step("a1", "Accepted", "0", "2"), step("bad", "Rejected", "0", "0")]
+    );
+}
+
+#[test]
+fn fills_are_reported_for_maker_and_taker() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.drain_reports();
+
+    let taker = Order::new("t1", Side::Bid, d("10"), d("5"), 2).with_time_in_force(TimeInForce::Ioc);
+    book.add_order(taker).unwrap();
+    assert_eq!(
+        lifecycle(&mut book),
+        vec![
+            step("t1", "Accepted", "0", "5"),
+            step("a1", "Filled", "2", "0"),
+            step("t1", "PartiallyFilled", "2", "3"),
+            step("a2", "Filled", "2", "0"),
+            step("t1", "PartiallyFilled", "4", "1"),
+            step("t1", "Cancelled", "4", "0"),
+        ]
+    );
+    assert!(book.drain_reports().is_empty());
+}
+
+#[test]
+fn expiry_amend_and_cancel_are_reported() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("g1", Side::Ask, d("11"), d("2"), 1).with_time_in_force(TimeInForce::Gtt(5)))
+        .unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("5"), d("2"), 6)).unwrap();
+    book.amend_order("b1", d("5"), d("1")).unwrap();
+    book.amend_order("b1", d("6"), d("1")).unwrap();
+    book.cancel_order("b1").unwrap();
+    assert_eq!(
+        lifecycle(&mut book),
+        vec![
+            step("g1", "Accepted", "0", "2"),
+            step("g1", "Expired", "0", "0"),
+            step("b1", "Accepted", "0", "2"),
+            step("b1", "Replaced", "0", "1"),
+            step("b1", "Replaced", "0", "1"),
+            step("b1", "Cancelled", "0", "0"),
+        ]
+    );
+}
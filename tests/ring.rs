@@ -0,0 +1,113 @@
+mod common;
+
+use coincidences::{Intent, RingSolver, SettlementPlan};
+use rust_decimal::Decimal;
+
+use common::d;
+
+/// Checks every fill against its intent's limit and every token for
+/// conservation.
+fn assert_sound(intents: &[Intent], plan: &SettlementPlan) {
+    for fill in plan.fills() {
+        let intent = intents.iter().find(|intent| intent.id() == fill.intent_id()).unwrap();
+        assert!(fill.sold() <= intent.sell_amount());
+        assert!(fill.bought() * intent.sell_amount() >= intent.buy_amount() * fill.sold());
+    }
+    for token in plan.prices().keys() {
+        let sold: Decimal = plan.fills().iter().filter(|fill| fill.sell_token() == token).map(|fill| fill.sold()).sum();
+        let bought: Decimal = plan.fills().iter().filter(|fill| fill.buy_token() == token).map(|fill| fill.bought()).sum();
+        assert_eq!(sold, bought, "{token} is not conserved");
+    }
+}
+
+#[test]
+fn ring_with_very_small_limit_prices_clears() {
+    for amount in ["100000000", "10000000000000"] {
+        let intents = vec![
+            Intent::new("1", "alice", "A", d(amount), "B", d("1")),
+            Intent::new("2", "bob", "B", d(amount), "C", d("1")),
+            Intent::new("3", "carol", "C", d(amount), "A", d("1")),
+        ];
+        let plan = RingSolver::new().solve(&intents);
+        assert_eq!(plan.fills().len(), 3, "ring selling {amount} for 1 should clear");
+        assert_sound(&intents, &plan);
+    }
+}
+
+#[test]
+fn ring_with_very_large_limit_prices_is_skipped() {
+    for amount in ["100000000", "10000000000000"] {
+        let intents = vec![
+            Intent::new("1", "alice", "A", d("1"), "B", d(amount)),
+            Intent::new("2", "bob", "B", d("1"), "C", d(amount)),
+            Intent::new("3", "carol", "C", d("1"), "A", d(amount)),
+        ];
+        assert!(RingSolver::new().solve(&intents).is_empty());
+    }
+}
+
+#[test]
+fn unrepresentable_limit_prices_are_ignored() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("0.0000000000000000000000000001"), "B", d("79228162514264337593543950335")),
+        Intent::new("2", "bob", "B", d("79228162514264337593543950335"), "A", d("0.0000000000000000000000000001")),
+        Intent::new("3", "carol", "A", d("1"), "B", d("1")),
+        Intent::new("4", "dave", "B", d("1"), "A", d("1")),
+    ];
+    let plan = RingSolver::new().solve(&intents);
+    assert_eq!(plan.fills().len(), 2);
+    assert!(plan.fills().iter().all(|fill| fill.intent_id() == "3" || fill.intent_id() == "4"));
+    assert_sound(&intents, &plan);
+}
+
+/// Checks every fill trades at the ratio of the plan's clearing prices.
+fn assert_uniform(plan: &SettlementPlan) {
+    for fill in plan.fills() {
+        let sell_price = plan.price(fill.sell_token()).unwrap();
+        let buy_price = plan.price(fill.buy_token()).unwrap();
+        let expected = fill.sold() * sell_price / buy_price;
+        assert!((expected - fill.bought()).abs() < d("0.000000001"), "{} is off price", fill.intent_id());
+    }
+}
+
+#[test]
+fn three_way_ring_clears_without_a_direct_match() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("10"), "B", d("18")),
+        Intent::new("2", "bob", "B", d("20"), "C", d("9")),
+        Intent::new("3", "carol", "C", d("10"), "A", d("9")),
+        Intent::new("4", "dave", "A", d("5"), "D", d("5")),
+    ];
+    let plan = RingSolver::new().solve(&intents);
+    let mut filled = plan.fills().iter().map(|fill| fill.intent_id()).collect::<Vec<_>>();
+    filled.sort();
+    assert_eq!(filled, vec!["1", "2", "3"]);
+    assert!(plan.fills().iter().all(|fill| fill.surplus() >= Decimal::ZERO));
+    assert!(plan.price("D").is_none());
+    assert_sound(&intents, &plan);
+    assert_uniform(&plan);
+}
+
+#[test]
+fn ring_whose_limits_cannot_all_be_met_is_skipped() {
+    // 1 A buys at least 2 B, but 1 B only buys 0.6 A back
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("1"), "B", d("2")),
+        Intent::new("2", "bob", "B", d("1"), "A", d("0.6")),
+    ];
+    assert!(RingSolver::new().solve(&intents).is_empty());
+}
+
+#[test]
+fn rings_sharing_a_token_share_its_price() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("1"), "B", d("1")),
+        Intent::new("2", "bob", "B", d("1"), "A", d("0.5")),
+        Intent::new("3", "carol", "A", d("1"), "C", d("1")),
+        Intent::new("4", "dave", "C", d("1"), "A", d("1")),
+    ];
+    let plan = RingSolver::new().solve(&intents);
+    assert_eq!(plan.fills().len(), 4);
+    assert_sound(&intents, &plan);
+    assert_uniform(&plan);
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use coincidences::{Execution, Order, OrderBook, ProRata, SelfTradePrevention, Side, TimeInForce};
+
+use common::d;
+
+fn book_with_asks(mode: SelfTradePrevention, accounts: &[&str]) -> OrderBook {
+    let mut book = OrderBook::new();
+    book.set_self_trade_prevention(mode);
+    for (timestamp, account) in accounts.iter().enumerate() {
+        let id = format!("a{timestamp}");
+        book.add_order(Order::new(id, Side::Ask, d("10"), d("5"), timestamp as u64).with_account(*account))
+            .unwrap();
+    }
+    book
+}
+
+fn fill_or_kill(account: &str, quantity: &str) -> Order {
+    Order::new("t", Side::Bid, d("10"), d(quantity), 10)
+        .with_account(account)
+        .with_time_in_force(TimeInForce::Fok)
+}
+
+#[test]
+fn fill_or_kill_ignores_own_liquidity() {
+    let mut book = book_with_asks(SelfTradePrevention::CancelNewest, &["A", "B"]);
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.cancelled_quantity(), d("10"));
+    assert!(book.order("a0").is_some());
+    assert!(book.order("a1").is_some());
+}
+
+#[test]
+fn fill_or_kill_is_killed_by_own_order_ahead_of_enough_liquidity() {
+    let mut book = book_with_asks(SelfTradePrevention::CancelNewest, &["B", "A", "C"]);
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(book.order("a0").map(Order::quantity), Some(d("5")));
+
+    let mut book = book_with_asks(SelfTradePrevention::DecrementAndCancel, &["A", "B", "C"]);
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert!(execution.trades().is_empty());
+}
+
+#[test]
+fn fill_or_kill_trades_past_own_order_it_cancels() {
+    let mut book = book_with_asks(SelfTradePrevention::CancelOldest, &["A", "B", "C"]);
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert_eq!(execution.filled_quantity(), d("10"));
+    assert_eq!(execution.cancelled_order_ids(), &["a1".to_string()]);
+
+    let mut book = book_with_asks(SelfTradePrevention::Allow, &["A", "B"]);
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert_eq!(execution.filled_quantity(), d("10"));
+}
+
+#[test]
+fn pro_rata_fill_or_kill_is_killed_by_own_order_anywhere_in_level() {
+    let mut book = book_with_asks(SelfTradePrevention::CancelNewest, &["A", "C", "B"]);
+    book.set_matching_policy(ProRata::new(d("1"))).unwrap();
+    let execution = book.add_order(fill_or_kill("B", "10")).unwrap();
+    assert!(execution.trades().is_empty());
+}
+
+/// Asks of 2 from A then B, hit by a bid for 3 from A.
+fn self_cross(mode: SelfTradePrevention, quantity: &str) -> (OrderBook, Execution) {
+    let mut book = OrderBook::new();
+    book.set_self_trade_prevention(mode);
+    book.add_order(Order::new("m1", Side::Ask, d("10"), d("2"), 1).with_account("A")).unwrap();
+    book.add_order(Order::new("m2", Side::Ask, d("10"), d("2"), 1).with_account("B")).unwrap();
+    let execution = book
+        .add_order(Order::new("t", Side::Bid, d("10"), d(quantity), 2).with_account("A"))
+        .unwrap();
+    (book, execution)
+}
+
+#[test]
+fn cancel_newest_cancels_the_incoming_order() {
+    let (book, execution) = self_cross(SelfTradePrevention::CancelNewest, "3");
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.cancelled_quantity(), d("3"));
+    assert!(book.order("m1").is_some());
+    assert_eq!(book.self_trade_prevention(), SelfTradePrevention::default());
+}
+
+#[test]
+fn cancel_oldest_cancels_the_resting_order_and_carries_on() {
+    let (book, execution) = self_cross(SelfTradePrevention::CancelOldest, "3");
+    assert_eq!(execution.filled_quantity(), d("2"));
+    assert_eq!(execution.cancelled_order_ids(), &["m1".to_string()]);
+    assert_eq!(execution.resting_quantity(), d("1"));
+    assert!(book.order("m1").is_none());
+}
+
+#[test]
+fn cancel_both_cancels_both_orders() {
+    let (book, execution) = self_cross(SelfTradePrevention::CancelBoth, "3");
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.cancelled_quantity(), d("3"));
+    assert!(book.order("m1").is_none());
+}
+
+#[test]
+fn decrement_and_cancel_takes_the_smaller_quantity_off_both() {
+    let (book, execution) = self_cross(SelfTradePrevention::DecrementAndCancel, "3");
+    assert_eq!(execution.filled_quantity(), d("1"));
+    assert_eq!(execution.cancelled_quantity(), d("2"));
+    assert!(book.order("m1").is_none());
+
+    let (book, execution) = self_cross(SelfTradePrevention::DecrementAndCancel, "1");
+    assert_eq!(execution.cancelled_quantity(), d("1"));
+    assert_eq!(book.order("m1").map(Order::quantity), Some(d("1")));
+}
+
+#[test]
+fn allow_lets_an_account_trade_with_itself() {
+    let (_, execution) = self_cross(SelfTradePrevention::Allow, "3");
+    assert_eq!(execution.filled_quantity(), d("3"));
+    assert_eq!(execution.trades()[0].maker_account(), Some("A"));
+    assert_eq!(execution.trades()[0].taker_account(), Some("A"));
+}
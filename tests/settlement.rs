@@ -0,0 +1,147 @@
+mod common;
+
+use coincidences::{
+    AccountLedger, InstrumentSpec, MatchingEngine, Order, OrderBook, OrderError, SettlementBatch, Side, TokenError,
+    TokenLedger, Trade,
+};
+use rust_decimal::Decimal;
+
+use common::d;
+
+/// Alice buys 5 from bob at 10, then sells 1 back to him at 9.
+fn trades() -> Vec<Trade> {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("5"), 1).with_account("bob")).unwrap();
+    let mut trades = Vec::new();
+    for (id, quantity, timestamp) in [("b1", "2", 2), ("b2", "3", 3)] {
+        let bid = Order::new(id, Side::Bid, d("10"), d(quantity), timestamp).with_account("alice");
+        trades.extend(book.add_order(bid).unwrap().into_trades());
+    }
+    book.add_order(Order::new("b3", Side::Bid, d("9"), d("1"), 4).with_account("bob")).unwrap();
+    let ask = Order::new("a2", Side::Ask, d("9"), d("1"), 5).with_account("alice");
+    trades.extend(book.add_order(ask).unwrap().into_trades());
+    trades
+}
+
+fn batch() -> SettlementBatch {
+    let mut batch = SettlementBatch::new();
+    batch.add_trades("ETH", "USD", &trades()).unwrap();
+    batch
+}
+
+fn positions(batch: &SettlementBatch) -> Vec<(String, String, Decimal)> {
+    batch
+        .positions()
+        .iter()
+        .map(|position| (position.account().to_string(), position.asset().to_string(), position.amount()))
+        .collect()
+}
+
+#[test]
+fn trades_net_to_one_transfer_per_account_and_asset() {
+    let batch = batch();
+    assert_eq!(batch.trade_count(), 3);
+    let position = |account: &str, asset: &str, amount| (account.to_string(), asset.to_string(), d(amount));
+    assert_eq!(
+        positions(&batch),
+        vec![
+            position("alice", "ETH", "4"),
+            position("alice", "USD", "-41"),
+            position("bob", "ETH", "-4"),
+            position("bob", "USD", "41"),
+        ]
+    );
+}
+
+#[test]
+fn trades_without_accounts_are_refused() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    let bid = Order::new("b1", Side::Bid, d("10"), d("1"), 2).with_account("alice");
+    let trades = book.add_order(bid).unwrap().into_trades();
+    assert_eq!(
+        SettlementBatch::new().add_trades("ETH", "USD", &trades),
+        Err(OrderError::MissingAccount("a1".to_string()))
+    );
+}
+
+#[test]
+fn ledger_settlement_is_all_or_nothing() {
+    let mut ledger = AccountLedger::new();
+    ledger.deposit("alice", "USD", d("40")).unwrap();
+    ledger.deposit("bob", "ETH", d("4")).unwrap();
+    assert_eq!(
+        batch().settle(&mut ledger).err(),
+        Some(OrderError::InsufficientFunds {
+            account: "alice".to_string(),
+            asset: "USD".to_string(),
+            required: d("41"),
+            available: d("40"),
+        })
+    );
+    assert_eq!(ledger.balance("bob", "ETH").available(), d("4"));
+
+    ledger.deposit("alice", "USD", d("1")).unwrap();
+    let report = batch().settle(&mut ledger).unwrap();
+    assert_eq!((report.gross_transfers(), report.net_transfers()), (6, 4));
+    assert_eq!(ledger.balance("alice", "ETH").available(), d("4"));
+    assert_eq!(ledger.balance("alice", "USD").available(), d("0"));
+    assert_eq!(ledger.balance("bob", "USD").available(), d("41"));
+    assert_eq!(ledger.balance("bob", "ETH").available(), d("0"));
+}
+
+#[test]
+fn token_settlement_pulls_through_allowances() {
+    let mut usd = TokenLedger::new("Dollar", "USD", 2);
+    let mut eth = TokenLedger::new("Ether", "ETH", 18);
+    usd.mint("alice", 4100).unwrap();
+    eth.mint("bob", eth.to_units(d("4")).unwrap()).unwrap();
+    usd.approve("alice", "clearing", u128::MAX).unwrap();
+    let mut tokens = vec![usd, eth];
+
+    assert_eq!(
+        batch().settle_tokens(&mut tokens, "clearing").err(),
+        Some(TokenError::InsufficientAllowance {
+            owner: "bob".to_string(),
+            spender: "clearing".to_string(),
+            allowance: 0,
+            needed: 4_000_000_000_000_000_000,
+        })
+    );
+    assert_eq!(tokens[0].balance_of("alice"), 4100);
+
+    tokens[1].approve("bob", "clearing", u128::MAX).unwrap();
+    batch().settle_tokens(&mut tokens, "clearing").unwrap();
+    assert_eq!(tokens[0].balance_of("bob"), 4100);
+    assert_eq!(tokens[1].balance_of("alice"), 4_000_000_000_000_000_000);
+    assert_eq!((tokens[0].balance_of("clearing"), tokens[1].balance_of("clearing")), (0, 0));
+
+    assert_eq!(
+        batch().settle_tokens(&mut tokens[..1], "clearing").err(),
+        Some(TokenError::UnknownToken("ETH".to_string()))
+    );
+}
+
+#[test]
+fn trades_the_engine_settled_are_refused() {
+    let mut engine = MatchingEngine::new();
+    engine.add_instrument("ETH-USD", InstrumentSpec::new().with_assets("ETH", "USD")).unwrap();
+    engine.ledger_mut().deposit("alice", "USD", d("100")).unwrap();
+    engine.ledger_mut().deposit("bob", "ETH", d("5")).unwrap();
+    engine
+        .add_order("ETH-USD", Order::new("a1", Side::Ask, d("10"), d("5"), 1).with_account("bob"))
+        .unwrap();
+    let bid = Order::new("b1", Side::Bid, d("10"), d("2"), 2).with_account("alice");
+    let settled = engine.add_order("ETH-USD", bid).unwrap().into_trades();
+    assert!(settled.iter().all(Trade::is_settled));
+    assert_eq!(engine.ledger().balance("alice", "ETH").available(), d("2"));
+
+    let mut batch = SettlementBatch::new();
+    assert_eq!(batch.add_trades("ETH", "USD", &settled), Err(OrderError::TradeAlreadySettled(1)));
+    assert_eq!(batch.trade_count(), 0);
+    assert!(!trades().iter().any(Trade::is_settled));
+
+    // The engine paid each side exactly once
+    assert_eq!(engine.ledger().balance("alice", "USD").available(), d("80"));
+    assert_eq!(engine.ledger().balance("bob", "USD").available(), d("20"));
+}
@@ -0,0 +1,101 @@
+mod common;
+
+use coincidences::{BookSnapshot, InstrumentSpec, Order, OrderBook, OrderError, Side, TimeInForce};
+
+use common::d;
+
+#[test]
+fn partly_filled_order_below_entry_minimums_restores() {
+    let spec = InstrumentSpec::new()
+        .with_lot_size(d("1"))
+        .with_min_quantity(d("10"))
+        .with_min_notional(d("100"));
+    let mut book = OrderBook::with_spec(spec).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("15"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("10"), 2)).unwrap();
+
+    let restored = OrderBook::from_snapshot(&book.snapshot()).unwrap();
+    assert_eq!(restored.order("a1").map(Order::quantity), Some(d("5")));
+    assert_eq!(restored.snapshot(), book.snapshot());
+}
+
+#[test]
+fn restore_still_checks_tick_and_lot() {
+    let spec = InstrumentSpec::new().with_tick_size(d("1")).with_lot_size(d("1"));
+    let off_lot = BookSnapshot::new(vec![], vec![Order::new("a1", Side::Ask, d("10"), d("2.5"), 1)], vec![])
+        .with_spec(spec.clone());
+    assert_eq!(
+        OrderBook::from_snapshot(&off_lot).err(),
+        Some(OrderError::OffLotQuantity {
+            quantity: d("2.5"),
+            lot_size: d("1"),
+        })
+    );
+    let off_tick = BookSnapshot::new(vec![Order::new("b1", Side::Bid, d("9.5"), d("2"), 1)], vec![], vec![])
+        .with_spec(spec);
+    assert_eq!(
+        OrderBook::from_snapshot(&off_tick).err(),
+        Some(OrderError::OffTickPrice {
+            price: d("9.5"),
+            tick_size: d("1"),
+        })
+    );
+}
+
+fn busy_book() -> OrderBook {
+    let mut book = OrderBook::with_tick_size(d("1")).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("5"), 2).with_display_quantity(d("2")))
+        .unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("10"), d("4"), 3).with_account("X")).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("9"), d("4"), 4)).unwrap();
+    book.add_order(Order::new("b2", Side::Bid, d("9"), d("4"), 5).with_time_in_force(TimeInForce::Gtt(100)))
+        .unwrap();
+    book.add_order(Order::new("t1", Side::Bid, d("10"), d("3"), 6)).unwrap();
+    book.add_order(Order::stop("s1", Side::Bid, d("20"), d("2"), 7)).unwrap();
+    book.cancel_order("b1").unwrap();
+    book
+}
+
+#[test]
+fn snapshot_lists_orders_in_queue_order() {
+    let snapshot = busy_book().snapshot();
+    let ids = |orders: &[Order]| orders.iter().map(|order| order.id().to_string()).collect::<Vec<_>>();
+    // a2's refreshed slice went behind a3
+    assert_eq!(ids(snapshot.asks()), vec!["a3", "a2"]);
+    assert_eq!(ids(snapshot.bids()), vec!["b2"]);
+    assert_eq!(ids(snapshot.stops()), vec!["s1"]);
+    assert_eq!(snapshot.last_price(), Some(d("10")));
+    assert_eq!(snapshot.clock(), 7);
+}
+
+#[test]
+fn restored_book_matches_like_the_original() {
+    let mut book = busy_book();
+    let snapshot = book.snapshot();
+    let mut restored = OrderBook::from_snapshot(&snapshot).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+    assert_eq!(restored.depth(10), book.depth(10));
+
+    let taker = Order::new("t2", Side::Bid, d("10"), d("6"), 8);
+    let original = book.add_order(taker.clone()).unwrap();
+    let replayed = restored.add_order(taker).unwrap();
+    assert_eq!(replayed.trades(), original.trades());
+    assert_eq!(restored.snapshot(), book.snapshot());
+    assert_eq!(restored.expire_orders(100), book.expire_orders(100));
+}
+
+#[test]
+fn snapshot_orders_that_cannot_rest_are_refused() {
+    let market = BookSnapshot::new(vec![Order::market("m1", Side::Bid, d("1"), 1)], vec![], vec![]);
+    assert_eq!(
+        OrderBook::from_snapshot(&market).err(),
+        Some(OrderError::CannotRest("m1".to_string()))
+    );
+    let duplicate = Order::new("a1", Side::Ask, d("10"), d("1"), 1);
+    let twice = BookSnapshot::new(vec![], vec![duplicate.clone(), duplicate], vec![]);
+    assert_eq!(
+        OrderBook::from_snapshot(&twice).err(),
+        Some(OrderError::DuplicateOrderId("a1".to_string()))
+    );
+}
@@ -0,0 +1,187 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use coincidences::{BookSolver, Intent, IntentFill, RingSolver, SettlementPlan, SolutionError, Solver, SolverCompetition};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn prices(prices: &[(&str, Decimal)]) -> BTreeMap<String, Decimal> {
+    prices.iter().map(|(token, price)| (token.to_string(), *price)).collect()
+}
+
+fn competition() -> SolverCompetition {
+    SolverCompetition::new()
+        .with_reference_price("A", d("1"))
+        .with_reference_price("B", d("1"))
+}
+
+#[test]
+fn fills_off_the_clearing_prices_are_rejected() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("10"), "B", d("10")),
+        Intent::new("2", "bob", "B", d("25"), "A", d("10")),
+    ];
+    // Balanced, and within both limits, but not at A = B
+    let plan = SettlementPlan::new(
+        prices(&[("A", d("1")), ("B", d("1"))]),
+        vec![
+            IntentFill::new(&intents[0], d("10"), d("25")),
+            IntentFill::new(&intents[1], d("25"), d("10")),
+        ],
+    );
+    assert_eq!(
+        competition().score(&intents, &plan),
+        Err(SolutionError::OffPrice {
+            intent: "1".to_string(),
+            bought: d("25"),
+            expected: d("10"),
+        })
+    );
+}
+
+#[test]
+fn rounding_in_derived_prices_is_tolerated() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("1"), "B", d("3")),
+        Intent::new("2", "bob", "B", d("3"), "A", d("1")),
+    ];
+    let plan = SettlementPlan::new(
+        prices(&[("A", d("1")), ("B", Decimal::ONE / d("3"))]),
+        vec![
+            IntentFill::new(&intents[0], d("1"), d("3")),
+            IntentFill::new(&intents[1], d("3"), d("1")),
+        ],
+    );
+    assert!(competition().score(&intents, &plan).is_ok());
+}
+
+/// Two direct swaps between A and D alongside a ring through A, B and C.
+fn batch() -> Vec<Intent> {
+    vec![
+        Intent::new("1", "alice", "A", d("10"), "B", d("18")),
+        Intent::new("2", "bob", "B", d("20"), "C", d("9")),
+        Intent::new("3", "carol", "C", d("10"), "A", d("9")),
+        Intent::new("4", "dave", "A", d("3"), "D", d("1")),
+        Intent::new("5", "erin", "D", d("1"), "A", d("2")),
+    ]
+}
+
+fn full_competition() -> SolverCompetition {
+    SolverCompetition::new()
+        .with_solver(BookSolver)
+        .with_solver(RingSolver::new())
+        .with_reference_price("A", d("1"))
+        .with_reference_price("B", d("0.5"))
+        .with_reference_price("C", d("1"))
+        .with_reference_price("D", d("2"))
+}
+
+#[test]
+fn the_plan_with_the_most_surplus_wins() {
+    let result = full_competition().run(&batch());
+    let solvers = result.submissions().iter().map(|submission| submission.solver()).collect::<Vec<_>>();
+    assert_eq!(solvers, vec!["book", "ring"]);
+    assert!(result.submissions().iter().all(|submission| submission.is_valid()));
+
+    // The book only sees the direct swap; the ring finds the cycle too
+    let book = &result.submissions()[0];
+    assert_eq!(book.plan().fills().len(), 2);
+    let winner = result.winner().unwrap();
+    assert_eq!(winner.solver(), "ring");
+    assert!(winner.score().unwrap() > book.score().unwrap());
+}
+
+#[test]
+fn tampered_plans_are_rejected() {
+    let intents = batch();
+    let competition = full_competition();
+    let prices = competition.run(&intents).submissions()[0].plan().prices().clone();
+    let plan = |fills| SettlementPlan::new(prices.clone(), fills);
+
+    assert_eq!(
+        competition.score(&intents, &plan(vec![IntentFill::new(&intents[3], d("3"), d("0.5"))])),
+        Err(SolutionError::LimitPriceViolated {
+            intent: "4".to_string(),
+            sold: d("3"),
+            bought: d("0.5"),
+        })
+    );
+    assert!(matches!(
+        competition.score(&intents, &plan(vec![IntentFill::new(&intents[3], d("3"), d("1.5"))])),
+        Err(SolutionError::OffPrice { .. })
+    ));
+    assert_eq!(
+        competition.score(&intents, &plan(vec![IntentFill::new(&intents[3], d("4"), d("2"))])),
+        Err(SolutionError::Overfill {
+            intent: "4".to_string(),
+            sold: d("4"),
+            available: d("3"),
+        })
+    );
+    let fill = IntentFill::new(&intents[3], d("3"), d("1"));
+    assert_eq!(
+        competition.score(&intents, &plan(vec![fill.clone(), fill])),
+        Err(SolutionError::DuplicateFill("4".to_string()))
+    );
+    let stranger = Intent::new("9", "mallory", "A", d("1"), "D", d("1"));
+    assert_eq!(
+        competition.score(&intents, &plan(vec![IntentFill::new(&stranger, d("1"), d("1"))])),
+        Err(SolutionError::UnknownIntent("9".to_string()))
+    );
+}
+
+/// Proposes a plan that fills one side of a swap and not the other.
+struct OneSided;
+
+impl Solver for OneSided {
+    fn name(&self) -> &str {
+        "one-sided"
+    }
+
+    fn solve(&self, intents: &[Intent]) -> SettlementPlan {
+        SettlementPlan::new(
+            prices(&[("A", d("1")), ("B", d("1"))]),
+            vec![IntentFill::new(&intents[0], d("10"), d("10"))],
+        )
+    }
+}
+
+#[test]
+fn invalid_plans_cannot_win() {
+    let intents = vec![
+        Intent::new("1", "alice", "A", d("10"), "B", d("10")),
+        Intent::new("2", "bob", "B", d("10"), "A", d("10")),
+    ];
+    let result = competition().with_solver(OneSided).with_solver(BookSolver).run(&intents);
+    assert_eq!(
+        result.submissions()[0].score(),
+        Err(&SolutionError::Unbalanced {
+            token: "A".to_string(),
+            sold: d("10"),
+            bought: d("0"),
+        })
+    );
+    assert_eq!(result.winner().map(|submission| submission.solver()), Some("book"));
+
+    // Nobody wins with nothing to settle
+    assert!(competition().with_solver(BookSolver).run(&[]).winner().is_none());
+}
+
+#[test]
+fn surplus_needs_a_reference_price() {
+    let intents = vec![
+        Intent::new("1", "a", "X", d("3"), "Y", d("1")),
+        Intent::new("2", "b", "Y", d("7"), "X", d("3")),
+    ];
+    let result = SolverCompetition::new().with_solver(BookSolver).run(&intents);
+    assert_eq!(result.submissions()[0].score(), Err(&SolutionError::NoReferencePrice("Y".to_string())));
+
+    // Prices that don't divide evenly still score
+    let competition = SolverCompetition::new()
+        .with_solver(BookSolver)
+        .with_reference_price("X", d("1"))
+        .with_reference_price("Y", d("1"));
+    assert!(competition.run(&intents).winner().is_some());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use coincidences::{Order, OrderBook, OrderError, OrderType, Side};
+use rust_decimal::Decimal;
+
+use common::d;
+
+fn book() -> OrderBook {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("11"), d("1"), 1)).unwrap();
+    book.add_order(Order::new("a3", Side::Ask, d("12"), d("5"), 1)).unwrap();
+    book
+}
+
+#[test]
+fn stops_wait_out_of_the_book_until_triggered() {
+    let mut book = book();
+    let execution = book.add_order(Order::stop("s1", Side::Bid, d("10"), d("1"), 2)).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(book.order("s1").map(Order::order_type), Some(OrderType::Stop));
+    assert!(book.best_bid().is_none());
+    assert_eq!(
+        book.add_order(Order::stop("s1", Side::Bid, d("20"), d("1"), 2)),
+        Err(OrderError::DuplicateOrderId("s1".to_string()))
+    );
+}
+
+#[test]
+fn triggered_stops_cascade_in_one_call() {
+    let mut book = book();
+    book.add_order(Order::stop("s1", Side::Bid, d("10"), d("1"), 2)).unwrap();
+    book.add_order(Order::stop_limit("s2", Side::Bid, d("11"), d("12"), d("2"), 2)).unwrap();
+    book.add_order(Order::stop("s3", Side::Bid, d("20"), d("2"), 2)).unwrap();
+
+    // The trade at 10 sets off s1, whose trade at 11 sets off s2
+    let execution = book.add_order(Order::new("b1", Side::Bid, d("10"), d("1"), 3)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("1"));
+    assert_eq!(execution.triggered_order_ids(), &["s1".to_string(), "s2".to_string()]);
+    let prices: Vec<Decimal> = execution.trades().iter().map(|trade| trade.price()).collect();
+    assert_eq!(prices, vec![d("10"), d("11"), d("12")]);
+    assert_eq!(book.last_price(), Some(d("12")));
+
+    assert!(book.order("s3").is_some());
+    book.cancel_order("s3").unwrap();
+    assert!(book.order("s3").is_none());
+}
+
+#[test]
+fn stop_already_through_the_last_price_triggers_on_entry() {
+    let mut book = book();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("1"), 2)).unwrap();
+    book.add_order(Order::new("b2", Side::Bid, d("5"), d("1"), 3)).unwrap();
+    let execution = book.add_order(Order::stop("s1", Side::Ask, d("13"), d("1"), 4)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("1"));
+    assert_eq!(execution.trades()[0].price(), d("5"));
+}
@@ -0,0 +1,86 @@
+mod common;
+
+use coincidences::{Order, OrderBook, OrderError, Side, TimeInForce};
+
+use common::d;
+
+fn book() -> OrderBook {
+    let mut book = OrderBook::with_tick_size(d("1")).unwrap();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1)).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("12"), d("2"), 1)).unwrap();
+    book
+}
+
+fn bid(id: &str, price: &str, quantity: &str, time_in_force: TimeInForce) -> Order {
+    Order::new(id, Side::Bid, d(price), d(quantity), 2).with_time_in_force(time_in_force)
+}
+
+#[test]
+fn immediate_or_cancel_never_rests() {
+    let mut book = book();
+    let execution = book.add_order(bid("i1", "11", "3", TimeInForce::Ioc)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("2"));
+    assert_eq!(execution.cancelled_quantity(), d("1"));
+    assert!(book.order("i1").is_none());
+}
+
+#[test]
+fn fill_or_kill_trades_all_or_nothing() {
+    let mut book = book();
+    let execution = book.add_order(bid("f1", "11", "3", TimeInForce::Fok)).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.cancelled_quantity(), d("3"));
+    assert_eq!(book.order("a1").map(Order::quantity), Some(d("2")));
+
+    let execution = book.add_order(bid("f2", "12", "3", TimeInForce::Fok)).unwrap();
+    assert_eq!(execution.filled_quantity(), d("3"));
+}
+
+#[test]
+fn post_only_is_rejected_or_slides_instead_of_taking() {
+    let mut book = book();
+    assert_eq!(
+        book.add_order(bid("p1", "10", "1", TimeInForce::PostOnly)),
+        Err(OrderError::WouldTakeLiquidity)
+    );
+    assert!(book.order("p1").is_none());
+
+    let execution = book.add_order(bid("p2", "10", "1", TimeInForce::PostOnlySlide)).unwrap();
+    assert!(execution.trades().is_empty());
+    assert_eq!(execution.resting_quantity(), d("1"));
+    assert_eq!(book.order("p2").and_then(Order::price), Some(d("9")));
+}
+
+#[test]
+fn post_only_amend_that_would_cross_is_refused() {
+    let mut book = book();
+    book.add_order(bid("p1", "8", "1", TimeInForce::PostOnly)).unwrap();
+    assert_eq!(book.amend_order("p1", d("10"), d("1")), Err(OrderError::WouldTakeLiquidity));
+    assert_eq!(book.order("p1").and_then(Order::price), Some(d("8")));
+}
+
+#[test]
+fn good_till_time_expires_once_the_clock_reaches_it() {
+    let mut book = book();
+    book.add_order(Order::new("g1", Side::Bid, d("5"), d("1"), 3).with_time_in_force(TimeInForce::Gtt(10)))
+        .unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("5"), d("1"), 9)).unwrap();
+    assert!(book.order("g1").is_some());
+
+    let execution = book.add_order(Order::new("b2", Side::Bid, d("5"), d("1"), 10)).unwrap();
+    assert_eq!(execution.expired_orders().iter().map(Order::id).collect::<Vec<_>>(), vec!["g1"]);
+    assert!(book.order("g1").is_none());
+
+    let late = Order::new("g2", Side::Bid, d("5"), d("1"), 10).with_time_in_force(TimeInForce::Gtt(10));
+    assert_eq!(book.add_order(late), Err(OrderError::InvalidTimeInForce(TimeInForce::Gtt(10))));
+}
+
+#[test]
+fn expire_orders_runs_without_new_orders() {
+    let mut book = book();
+    book.add_order(Order::new("g1", Side::Bid, d("5"), d("1"), 3).with_time_in_force(TimeInForce::Gtt(10)))
+        .unwrap();
+    assert!(book.expire_orders(9).is_empty());
+    assert_eq!(book.expire_orders(10).len(), 1);
+    assert_eq!(book.clock(), 10);
+}
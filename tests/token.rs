@@ -0,0 +1,104 @@
+mod common;
+
+use coincidences::{TokenError, TokenEvent, TokenLedger, ZERO_ADDRESS};
+
+use common::d;
+
+fn usdc() -> TokenLedger {
+    TokenLedger::new("USD Coin", "USDC", 6)
+}
+
+#[test]
+fn amounts_convert_through_the_decimals() {
+    let token = usdc();
+    assert_eq!(token.to_units(d("1.5")), Ok(1_500_000));
+    assert_eq!(token.from_units(1_500_000), Some(d("1.5")));
+    assert_eq!(
+        token.to_units(d("0.0000001")),
+        Err(TokenError::UnrepresentableAmount(d("0.0000001")))
+    );
+    assert_eq!(token.to_units(d("-1")), Err(TokenError::UnrepresentableAmount(d("-1"))));
+
+    let wei = TokenLedger::new("Ether", "ETH", 18);
+    assert_eq!(wei.to_units(d("2")), Ok(2_000_000_000_000_000_000));
+    // 10^17 whole tokens needs 35 digits, more than a Decimal holds
+    assert!(wei.to_units(d("100000000000000000")).is_err());
+    assert_eq!(wei.from_units(u128::MAX), None);
+}
+
+#[test]
+fn transfers_move_balances_and_emit_events() {
+    let mut token = usdc();
+    token.mint("alice", 100).unwrap();
+    token.transfer("alice", "bob", 30).unwrap();
+    assert_eq!(
+        token.transfer("alice", "bob", 71),
+        Err(TokenError::InsufficientBalance {
+            owner: "alice".to_string(),
+            balance: 70,
+            needed: 71,
+        })
+    );
+    token.burn("bob", 10).unwrap();
+    assert_eq!((token.balance_of("alice"), token.balance_of("bob")), (70, 20));
+    assert_eq!(token.total_supply(), 90);
+
+    let transfer = |from: &str, to: &str, value| TokenEvent::Transfer {
+        from: from.to_string(),
+        to: to.to_string(),
+        value,
+    };
+    assert_eq!(
+        token.drain_events(),
+        vec![
+            transfer(ZERO_ADDRESS, "alice", 100),
+            transfer("alice", "bob", 30),
+            transfer("bob", ZERO_ADDRESS, 10),
+        ]
+    );
+    assert!(token.drain_events().is_empty());
+}
+
+#[test]
+fn the_zero_address_holds_nothing() {
+    let mut token = usdc();
+    token.mint("alice", 100).unwrap();
+    assert_eq!(
+        token.mint(ZERO_ADDRESS, 1),
+        Err(TokenError::InvalidReceiver(ZERO_ADDRESS.to_string()))
+    );
+    assert_eq!(
+        token.transfer("alice", ZERO_ADDRESS, 1),
+        Err(TokenError::InvalidReceiver(ZERO_ADDRESS.to_string()))
+    );
+    assert_eq!(
+        token.approve("alice", ZERO_ADDRESS, 1),
+        Err(TokenError::InvalidSpender(ZERO_ADDRESS.to_string()))
+    );
+    assert_eq!(token.balance_of("alice"), 100);
+}
+
+#[test]
+fn allowances_limit_transfer_from() {
+    let mut token = usdc();
+    token.mint("alice", 100).unwrap();
+    token.mint("bob", 30).unwrap();
+    token.approve("alice", "exchange", 50).unwrap();
+    assert_eq!(
+        token.transfer_from("exchange", "alice", "carol", 51),
+        Err(TokenError::InsufficientAllowance {
+            owner: "alice".to_string(),
+            spender: "exchange".to_string(),
+            allowance: 50,
+            needed: 51,
+        })
+    );
+    token.transfer_from("exchange", "alice", "carol", 20).unwrap();
+    assert_eq!(token.allowance("alice", "exchange"), 30);
+
+    // An infinite allowance is never spent down
+    token.approve("bob", "exchange", u128::MAX).unwrap();
+    token.transfer_from("exchange", "bob", "carol", 30).unwrap();
+    assert_eq!(token.allowance("bob", "exchange"), u128::MAX);
+    assert_eq!(token.balance_of("carol"), 50);
+}
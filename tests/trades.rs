@@ -0,0 +1,46 @@
+mod common;
+
+use coincidences::{Order, OrderBook, Side};
+
+use common::d;
+
+#[test]
+fn trades_carry_ids_sides_times_and_sequences() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("2"), 1).with_account("M")).unwrap();
+    book.add_order(Order::new("a2", Side::Ask, d("10"), d("2"), 3)).unwrap();
+    let trades = book
+        .add_order(Order::new("b1", Side::Bid, d("11"), d("3"), 7).with_account("T"))
+        .unwrap()
+        .into_trades();
+
+    assert_eq!(trades.iter().map(|trade| trade.id()).collect::<Vec<_>>(), vec![1, 2]);
+    assert!(trades.iter().all(|trade| trade.aggressor() == Side::Bid));
+    assert!(trades.iter().all(|trade| trade.timestamp() == 7 && trade.sequence() == 3));
+    assert_eq!(trades[0].price(), d("10"));
+    assert_eq!((trades[0].maker_account(), trades[0].taker_account()), (Some("M"), Some("T")));
+    assert_eq!((trades[0].maker_remaining(), trades[0].taker_remaining()), (d("0"), d("1")));
+    assert_eq!((trades[1].maker_remaining(), trades[1].taker_remaining()), (d("1"), d("0")));
+}
+
+#[test]
+fn maker_remaining_includes_the_hidden_reserve() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("ice", Side::Bid, d("10"), d("5"), 1).with_display_quantity(d("2")))
+        .unwrap();
+    let trades = book.add_order(Order::new("a1", Side::Ask, d("10"), d("1"), 2)).unwrap().into_trades();
+    assert_eq!(trades[0].aggressor(), Side::Ask);
+    assert_eq!(trades[0].maker_remaining(), d("4"));
+}
+
+#[test]
+fn ids_and_times_continue_after_a_restore() {
+    let mut book = OrderBook::new();
+    book.add_order(Order::new("a1", Side::Ask, d("10"), d("4"), 1)).unwrap();
+    book.add_order(Order::new("b1", Side::Bid, d("10"), d("1"), 7)).unwrap();
+
+    let mut restored = OrderBook::from_snapshot(&book.snapshot()).unwrap();
+    // An earlier timestamp never moves book time backwards
+    let trades = restored.add_order(Order::new("b2", Side::Bid, d("10"), d("1"), 2)).unwrap().into_trades();
+    assert_eq!((trades[0].id(), trades[0].timestamp(), trades[0].sequence()), (2, 7, 3));
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use coincidences::{AccountLedger, TokenError, TokenLedger, Vault, VaultEvent};
+
+use common::d;
+
+/// A vault with three extra share decimals over a 6-decimal dollar, with
+/// alice and bob each holding 1,000 units and having approved it.
+fn vault() -> Vault {
+    let mut usd = TokenLedger::new("Dollar", "USD", 6);
+    for owner in ["alice", "bob"] {
+        usd.mint(owner, 1_000).unwrap();
+        usd.approve(owner, "vault", u128::MAX).unwrap();
+    }
+    Vault::new("vault", usd, "Vault USD", "vUSD").with_decimals_offset(3)
+}
+
+#[test]
+fn first_deposit_mints_at_the_virtual_price() {
+    let mut vault = vault();
+    assert_eq!(vault.shares().decimals(), 9);
+    assert_eq!(vault.deposit("alice", 100, "alice"), Ok(100_000));
+    assert_eq!(vault.shares().balance_of("alice"), 100_000);
+    assert_eq!(vault.total_assets(), 100);
+    assert_eq!(vault.max_withdraw("alice"), Ok(100));
+    assert_eq!(
+        vault.drain_events(),
+        vec![VaultEvent::Deposit {
+            sender: "alice".to_string(),
+            owner: "alice".to_string(),
+            assets: 100,
+            shares: 100_000,
+        }]
+    );
+}
+
+#[test]
+fn previews_round_in_the_vaults_favour() {
+    let mut vault = vault();
+    vault.deposit("alice", 100, "alice").unwrap();
+    // A donation raises the share price to 151 assets per 101,000 shares
+    vault.asset_mut().transfer("bob", "vault", 50).unwrap();
+
+    assert_eq!(vault.preview_deposit(100), Ok(66_887));
+    assert_eq!(vault.preview_mint(66_887), Ok(100));
+    assert_eq!(vault.convert_to_shares(10), Ok(6_688));
+    assert_eq!(vault.preview_withdraw(10), Ok(6_689));
+    assert_eq!(vault.preview_redeem(100_000), Ok(149));
+
+    assert_eq!(vault.mint("bob", 66_887, "bob"), Ok(100));
+    assert_eq!(vault.redeem("alice", 100_000, "alice", "alice"), Ok(149));
+    assert_eq!(vault.asset().balance_of("alice"), 1_049);
+}
+
+#[test]
+fn donating_to_an_empty_vault_does_not_pay() {
+    let mut vault = vault();
+    vault.deposit("bob", 1, "bob").unwrap();
+    vault.asset_mut().transfer("bob", "vault", 999).unwrap();
+    let shares = vault.deposit("alice", 1_000, "alice").unwrap();
+    assert!(shares > 0);
+
+    // The attacker gets back less than they put in
+    let recovered = vault.redeem("bob", 1_000, "bob", "bob").unwrap();
+    assert!(recovered < 1_000);
+    assert!(vault.max_withdraw("alice").unwrap() > 900);
+}
+
+#[test]
+fn redeeming_for_another_owner_spends_their_allowance() {
+    let mut vault = vault();
+    vault.deposit("bob", 100, "bob").unwrap();
+    assert_eq!(
+        vault.redeem("carol", 1_000, "carol", "bob"),
+        Err(TokenError::InsufficientAllowance {
+            owner: "bob".to_string(),
+            spender: "carol".to_string(),
+            allowance: 0,
+            needed: 1_000,
+        })
+    );
+    vault.shares_mut().approve("bob", "carol", 1_000).unwrap();
+    vault.drain_events();
+    assert_eq!(vault.redeem("carol", 1_000, "carol", "bob"), Ok(1));
+    assert_eq!(vault.shares().allowance("bob", "carol"), 0);
+    assert_eq!(vault.asset().balance_of("carol"), 1);
+    assert_eq!(
+        vault.drain_events(),
+        vec![VaultEvent::Withdraw {
+            sender: "carol".to_string(),
+            receiver: "carol".to_string(),
+            owner: "bob".to_string(),
+            assets: 1,
+            shares: 1_000,
+        }]
+    );
+}
+
+/// Applies the vault's pending events to the ledger.
+fn sync(vault: &mut Vault, ledger: &mut AccountLedger) {
+    for event in vault.drain_events() {
+        let (owner, change) = vault.custody_change(&event).unwrap();
+        ledger.apply_custody(&owner, "USD", change).unwrap();
+    }
+}
+
+#[test]
+fn custody_changes_feed_the_ledger() {
+    let mut vault = vault();
+    let mut ledger = AccountLedger::new();
+    vault.deposit("bob", 250, "bob").unwrap();
+    sync(&mut vault, &mut ledger);
+    assert_eq!(ledger.balance("bob", "USD").available(), d("0.00025"));
+
+    // Funds that left the ledger stay gone after a later custody change
+    ledger.withdraw("bob", "USD", d("0.0002")).unwrap();
+    vault.deposit("bob", 100, "bob").unwrap();
+    sync(&mut vault, &mut ledger);
+    assert_eq!(ledger.balance("bob", "USD").available(), d("0.00015"));
+
+    vault.withdraw("bob", 150, "bob", "bob").unwrap();
+    sync(&mut vault, &mut ledger);
+    assert_eq!(ledger.balance("bob", "USD").available(), d("0"));
+    vault.withdraw("bob", 1, "bob", "bob").unwrap();
+    let event = vault.drain_events().pop().unwrap();
+    assert_eq!(vault.custody_change(&event), Some(("bob".to_string(), d("-0.000001"))));
+    assert!(ledger.apply_custody("bob", "USD", d("-0.000001")).is_err());
+}
+
+#[test]
+fn conversions_use_full_width_arithmetic() {
+    let mut asset = TokenLedger::new("A", "A", 0);
+    asset.mint("x", u128::MAX / 3).unwrap();
+    asset.approve("x", "vault", u128::MAX).unwrap();
+    let mut vault = Vault::new("vault", asset, "S", "S");
+    assert_eq!(vault.convert_to_shares(u128::MAX), Ok(u128::MAX));
+    vault.deposit("x", u128::MAX / 3, "x").unwrap();
+    assert_eq!(vault.convert_to_assets(u128::MAX / 5), Ok(u128::MAX / 5));
+
+    let offset = Vault::new("vault", TokenLedger::new("A", "A", 0), "S", "S").with_decimals_offset(3);
+    assert_eq!(offset.convert_to_shares(u128::MAX / 1000), Ok(u128::MAX / 1000 * 1000));
+    assert_eq!(offset.convert_to_shares(u128::MAX), Err(TokenError::ArithmeticOverflow));
+}